@@ -0,0 +1,347 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::Deserialize;
+
+use super::{tree::Status, PatrolRoute};
+use crate::{
+    health::Damage,
+    navigation::NavAgent,
+    perception::{AwarenessLevel, Perception},
+    player::CharacterInput,
+};
+
+const TARGET: &str = "target";
+const PATROL_INDEX: &str = "patrol_index";
+const FLEEING: &str = "fleeing";
+const LOOK_AROUND_UNTIL: &str = "look_around_until";
+const LOOK_AROUND_YAWN: &str = "look_around_yawn";
+
+const ARRIVAL_DISTANCE: f32 = 0.3;
+const REPATH_DISTANCE: f32 = 0.5;
+const LOOK_AROUND_ANGLE: f32 = 1.2;
+const LOOK_AROUND_SPEED: f32 = 2.0;
+
+#[derive(Clone, Copy, Debug)]
+pub enum BlackboardValue {
+    Entity(Entity),
+    Number(f32),
+}
+
+/// Per-character memory shared by every node of its tree.
+#[derive(Default, Debug)]
+pub struct Blackboard(HashMap<String, BlackboardValue>);
+
+impl Blackboard {
+    pub fn set(&mut self, key: &str, value: BlackboardValue) {
+        self.0.insert(key.to_owned(), value);
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.0.remove(key);
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.0.contains_key(key)
+    }
+
+    pub fn entity(&self, key: &str) -> Option<Entity> {
+        match self.0.get(key) {
+            Some(BlackboardValue::Entity(entity)) => Some(*entity),
+            _ => None,
+        }
+    }
+
+    pub fn number(&self, key: &str) -> Option<f32> {
+        match self.0.get(key) {
+            Some(BlackboardValue::Number(number)) => Some(*number),
+            _ => None,
+        }
+    }
+}
+
+/// Everything a leaf may read or steer while its character's tree ticks.
+pub struct Context<'a> {
+    pub transform: &'a Transform,
+    pub health_fraction: f32,
+    pub patrol_route: Option<&'a PatrolRoute>,
+    pub perception: Option<&'a Perception>,
+    pub players: &'a [(Entity, Vec3)],
+    pub blackboard: &'a mut Blackboard,
+    pub nav_agent: &'a mut NavAgent,
+    pub input: &'a mut CharacterInput,
+    pub damage: &'a mut Vec<Damage>,
+    pub now: f32,
+}
+
+impl Context<'_> {
+    fn position(&self) -> Vec3 {
+        self.transform.translation
+    }
+
+    fn target_position(&self) -> Option<Vec3> {
+        let target = self.blackboard.entity(TARGET)?;
+
+        self.players
+            .iter()
+            .find(|(player, _)| *player == target)
+            .map(|(_, position)| *position)
+    }
+
+    /// Walks towards `destination`, only asking for a new path when it moved
+    /// far enough from the current one.
+    fn go_to(&mut self, destination: Vec3) {
+        let outdated = self
+            .nav_agent
+            .destination()
+            .is_none_or(|current| current.distance(destination) > REPATH_DISTANCE);
+
+        if outdated {
+            self.nav_agent.set_destination(Some(destination));
+        }
+    }
+
+    fn stop(&mut self) {
+        if self.nav_agent.destination().is_some() {
+            self.nav_agent.set_destination(None);
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub enum Condition {
+    /// Health is below this fraction of the maximum.
+    HealthBelow(f32),
+    /// The blackboard target is closer than this distance.
+    TargetWithin(f32),
+    /// The blackboard has a value for this key.
+    IsSet(String),
+    /// The `Perception` of the character reached at least this level.
+    Awareness(AwarenessLevel),
+}
+
+impl Condition {
+    pub fn check(&self, context: &Context) -> bool {
+        match self {
+            Condition::HealthBelow(fraction) => context.health_fraction < *fraction,
+            Condition::TargetWithin(distance) => context
+                .target_position()
+                .is_some_and(|target| target.distance(context.position()) <= *distance),
+            Condition::IsSet(key) => context.blackboard.contains(key),
+            Condition::Awareness(level) => context
+                .perception
+                .is_some_and(|perception| perception.level() >= *level),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub enum Action {
+    /// Remembers the closest player within `range` as the target, fails
+    /// without forgetting the previous target when nobody is in range.
+    FindPlayer { range: f32 },
+    /// Remembers the player in sight of the `Perception` as the target.
+    TargetPerceived,
+    /// Walks to where the `Perception` last saw or heard a player.
+    Investigate,
+    /// Walks to the next waypoint of the `PatrolRoute`.
+    Patrol,
+    /// Runs after the target until it gets further than `give_up_range`.
+    Chase { give_up_range: f32 },
+    /// Hits the target once, pair it with a `Cooldown`.
+    Attack { damage: f32 },
+    /// Runs `distance` away from the closest player.
+    Flee { distance: f32 },
+    /// Stands still and sweeps its gaze left and right.
+    LookAround { seconds: f32 },
+}
+
+impl Action {
+    pub fn run(&self, context: &mut Context) -> Status {
+        match self {
+            Action::FindPlayer { range } => find_player(context, *range),
+            Action::TargetPerceived => target_perceived(context),
+            Action::Investigate => investigate(context),
+            Action::Patrol => patrol(context),
+            Action::Chase { give_up_range } => chase(context, *give_up_range),
+            Action::Attack { damage } => attack(context, *damage),
+            Action::Flee { distance } => flee(context, *distance),
+            Action::LookAround { seconds } => look_around(context, *seconds),
+        }
+    }
+}
+
+fn find_player(context: &mut Context, range: f32) -> Status {
+    let position = context.position();
+    let closest = context
+        .players
+        .iter()
+        .filter(|(_, player)| player.distance(position) <= range)
+        .min_by(|(_, a), (_, b)| a.distance(position).total_cmp(&b.distance(position)));
+
+    match closest {
+        Some((player, _)) => {
+            context
+                .blackboard
+                .set(TARGET, BlackboardValue::Entity(*player));
+            Status::Success
+        }
+        None => Status::Failure,
+    }
+}
+
+fn target_perceived(context: &mut Context) -> Status {
+    match context.perception.and_then(Perception::target) {
+        Some(player) => {
+            context
+                .blackboard
+                .set(TARGET, BlackboardValue::Entity(player));
+            Status::Success
+        }
+        None => Status::Failure,
+    }
+}
+
+fn investigate(context: &mut Context) -> Status {
+    let Some(position) = context.perception.and_then(Perception::last_known_position) else {
+        return Status::Failure;
+    };
+
+    if position.xz().distance(context.position().xz()) <= ARRIVAL_DISTANCE {
+        context.stop();
+        return Status::Success;
+    }
+
+    context.go_to(position);
+    Status::Running
+}
+
+fn patrol(context: &mut Context) -> Status {
+    let Some(waypoints) = context.patrol_route.map(|route| &route.0) else {
+        return Status::Failure;
+    };
+
+    if waypoints.is_empty() {
+        return Status::Failure;
+    }
+
+    let index = context.blackboard.number(PATROL_INDEX).unwrap_or(0.0) as usize % waypoints.len();
+    let waypoint = waypoints[index];
+
+    if waypoint.xz().distance(context.position().xz()) <= ARRIVAL_DISTANCE {
+        context.blackboard.set(
+            PATROL_INDEX,
+            BlackboardValue::Number(((index + 1) % waypoints.len()) as f32),
+        );
+        context.stop();
+
+        return Status::Success;
+    }
+
+    context.go_to(waypoint);
+    Status::Running
+}
+
+fn chase(context: &mut Context, give_up_range: f32) -> Status {
+    let in_range = context
+        .target_position()
+        .filter(|target| target.distance(context.position()) <= give_up_range);
+
+    let Some(target) = in_range else {
+        context.blackboard.remove(TARGET);
+        context.stop();
+
+        return Status::Failure;
+    };
+
+    context.go_to(target);
+    context.input.run = true;
+
+    Status::Running
+}
+
+fn attack(context: &mut Context, damage: f32) -> Status {
+    let Some(target) = context.target_position() else {
+        return Status::Failure;
+    };
+
+    let Some(entity) = context.blackboard.entity(TARGET) else {
+        return Status::Failure;
+    };
+
+    context.stop();
+    context.input.look = Some((target - context.position()).normalize_or_zero());
+    context.damage.push(Damage {
+        entity,
+        amount: damage,
+    });
+
+    Status::Success
+}
+
+fn flee(context: &mut Context, distance: f32) -> Status {
+    // Once the agent dropped its destination the escape is over, whether it
+    // arrived or couldn't find a way.
+    if context.blackboard.contains(FLEEING) {
+        if context.nav_agent.destination().is_some() {
+            context.input.run = true;
+            return Status::Running;
+        }
+
+        context.blackboard.remove(FLEEING);
+        return Status::Success;
+    }
+
+    let position = context.position();
+    let Some(threat) = context
+        .players
+        .iter()
+        .map(|(_, player)| *player)
+        .min_by(|a, b| a.distance(position).total_cmp(&b.distance(position)))
+    else {
+        return Status::Failure;
+    };
+
+    let mut away = position - threat;
+    away.y = 0.0;
+    let away = away.try_normalize().unwrap_or(Vec3::X);
+
+    context
+        .nav_agent
+        .set_destination(Some(position + away * distance));
+    context
+        .blackboard
+        .set(FLEEING, BlackboardValue::Number(1.0));
+    context.input.run = true;
+
+    Status::Running
+}
+
+fn look_around(context: &mut Context, seconds: f32) -> Status {
+    let Some(until) = context.blackboard.number(LOOK_AROUND_UNTIL) else {
+        let forward = *context.transform.back();
+        let yawn = forward.x.atan2(forward.z);
+
+        context.blackboard.set(
+            LOOK_AROUND_UNTIL,
+            BlackboardValue::Number(context.now + seconds),
+        );
+        context
+            .blackboard
+            .set(LOOK_AROUND_YAWN, BlackboardValue::Number(yawn));
+        context.stop();
+
+        return Status::Running;
+    };
+
+    if context.now >= until {
+        context.blackboard.remove(LOOK_AROUND_UNTIL);
+        context.blackboard.remove(LOOK_AROUND_YAWN);
+
+        return Status::Success;
+    }
+
+    let yawn = context.blackboard.number(LOOK_AROUND_YAWN).unwrap_or(0.0)
+        + (context.now * LOOK_AROUND_SPEED).sin() * LOOK_AROUND_ANGLE;
+    context.input.look = Some(Vec3::new(yawn.sin(), 0.0, yawn.cos()));
+
+    Status::Running
+}
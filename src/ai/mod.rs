@@ -0,0 +1,158 @@
+use bevy::prelude::*;
+
+mod leaves;
+mod tree;
+
+use crate::{
+    health::{Damage, Died, Health},
+    local_player::PlayerInput,
+    lock_on::Targetable,
+    navigation::NavAgent,
+    perception::{Perception, PerceptionDescriptor},
+    player::{character_bundle, CharacterInput},
+    schedule::{CharacterSet, InGameSet},
+};
+use leaves::{Blackboard, Context};
+use tree::{BehaviourTree, BehaviourTreeLoader, NodeMemory};
+
+/// Runs a behaviour tree loaded from a `.bt.ron` file to drive its character.
+#[derive(Component)]
+pub struct Brain {
+    tree: Handle<BehaviourTree>,
+    blackboard: Blackboard,
+    memory: NodeMemory,
+}
+
+impl Brain {
+    pub fn new(tree: Handle<BehaviourTree>) -> Brain {
+        Brain {
+            tree,
+            blackboard: Blackboard::default(),
+            memory: NodeMemory::default(),
+        }
+    }
+}
+
+/// Waypoints the `Patrol` leaf walks through in a loop.
+#[derive(Component)]
+pub struct PatrolRoute(pub Vec<Vec3>);
+
+#[derive(Component)]
+struct Enemy;
+
+pub struct AiPlugin;
+
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<BehaviourTree>()
+            .init_asset_loader::<BehaviourTreeLoader>()
+            .add_systems(Startup, spawn_enemies)
+            .add_systems(Update, run_behaviour_trees.in_set(CharacterSet::Input))
+            .add_systems(
+                Update,
+                despawn_dead_enemies.in_set(InGameSet::EntityUpdates),
+            );
+    }
+}
+
+fn spawn_enemies(mut commands: Commands, assets: Res<AssetServer>) {
+    let enemies = [(
+        "Grunt",
+        vec![
+            Vec3::new(-3.0, 0.0, 3.2),
+            Vec3::new(-3.0, 0.0, -1.0),
+            Vec3::new(0.0, 0.0, 3.2),
+        ],
+    )];
+
+    for (name, route) in enemies {
+        let enemy = commands.spawn_empty().id();
+
+        commands.entity(enemy).insert((
+            character_bundle(&assets, Transform::from_translation(route[0]), enemy),
+            Brain::new(assets.load("ai/grunt.bt.ron")),
+            PatrolRoute(route),
+            NavAgent::default(),
+            Perception::new(PerceptionDescriptor {
+                fov: 1.8,
+                sight_range: 8.0,
+                hearing_radius: 6.0,
+                gain_rate: 1.5,
+                decay_rate: 0.15,
+            }),
+            Health::new(100.0),
+            Targetable,
+            Enemy,
+            Name::new(name),
+        ));
+    }
+}
+
+fn run_behaviour_trees(
+    mut brain_query: Query<(
+        Entity,
+        &mut Brain,
+        &Transform,
+        &Health,
+        &mut NavAgent,
+        &mut CharacterInput,
+    )>,
+    route_query: Query<&PatrolRoute>,
+    perception_query: Query<&Perception>,
+    // Cameras and camera targets share `LocalPlayer`, only the characters
+    // themselves read `PlayerInput`.
+    player_query: Query<(Entity, &GlobalTransform), With<PlayerInput>>,
+    trees: Res<Assets<BehaviourTree>>,
+    mut damage_events: EventWriter<Damage>,
+    time: Res<Time>,
+) {
+    let players = player_query
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation()))
+        .collect::<Vec<_>>();
+    let mut damage = Vec::new();
+
+    for (entity, mut brain, transform, health, mut nav_agent, mut input) in brain_query.iter_mut() {
+        let Some(tree) = trees.get(&brain.tree) else {
+            continue;
+        };
+
+        // Leaves only set what they need, anything else falls back to
+        // walking quietly.
+        input.look = None;
+        input.run = false;
+
+        let Brain {
+            blackboard, memory, ..
+        } = brain.as_mut();
+
+        let mut context = Context {
+            transform,
+            health_fraction: health.fraction(),
+            patrol_route: route_query.get(entity).ok(),
+            perception: perception_query.get(entity).ok(),
+            players: &players,
+            blackboard,
+            nav_agent: &mut nav_agent,
+            input: &mut input,
+            damage: &mut damage,
+            now: time.elapsed_seconds(),
+        };
+
+        tree.tick(memory, &mut context);
+    }
+
+    damage_events.send_batch(damage);
+}
+
+fn despawn_dead_enemies(
+    mut commands: Commands,
+    mut died_events: EventReader<Died>,
+    enemy_query: Query<(), With<Enemy>>,
+) {
+    for died in died_events.read() {
+        if enemy_query.contains(died.entity) {
+            commands.entity(died.entity).despawn_recursive();
+        }
+    }
+}
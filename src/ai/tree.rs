@@ -0,0 +1,223 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::{BoxedFuture, HashMap},
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::leaves::{Action, Condition, Context};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Status {
+    Success,
+    Failure,
+    Running,
+}
+
+/// Node layout of a `.bt.ron` file.
+#[derive(Deserialize)]
+enum NodeDefinition {
+    /// Ticks its children in order until one doesn't fail.
+    Selector(Vec<NodeDefinition>),
+    /// Ticks its children in order until one doesn't succeed, starting over
+    /// every frame so earlier conditions can interrupt running children.
+    Sequence(Vec<NodeDefinition>),
+    /// Like `Sequence` but resumes from the child that was running.
+    MemorySequence(Vec<NodeDefinition>),
+    Inverter(Box<NodeDefinition>),
+    /// Turns a failure of its child into a success.
+    Succeeder(Box<NodeDefinition>),
+    /// Fails until `seconds` have passed since its child last succeeded.
+    Cooldown {
+        seconds: f32,
+        child: Box<NodeDefinition>,
+    },
+    Condition(Condition),
+    Action(Action),
+}
+
+#[derive(Deserialize)]
+struct TreeDefinition {
+    root: NodeDefinition,
+}
+
+enum NodeKind {
+    Selector(Vec<Node>),
+    Sequence(Vec<Node>),
+    MemorySequence(Vec<Node>),
+    Inverter(Box<Node>),
+    Succeeder(Box<Node>),
+    Cooldown { seconds: f32, child: Box<Node> },
+    Condition(Condition),
+    Action(Action),
+}
+
+/// Every node gets a unique id so the nodes that need to remember something
+/// between frames can keep it in the `Brain` running the tree.
+struct Node {
+    id: usize,
+    kind: NodeKind,
+}
+
+impl Node {
+    fn new(definition: NodeDefinition, next_id: &mut usize) -> Node {
+        let id = *next_id;
+        *next_id += 1;
+
+        let mut children = |definitions: Vec<NodeDefinition>| {
+            definitions
+                .into_iter()
+                .map(|definition| Node::new(definition, next_id))
+                .collect::<Vec<_>>()
+        };
+
+        let kind = match definition {
+            NodeDefinition::Selector(definitions) => NodeKind::Selector(children(definitions)),
+            NodeDefinition::Sequence(definitions) => NodeKind::Sequence(children(definitions)),
+            NodeDefinition::MemorySequence(definitions) => {
+                NodeKind::MemorySequence(children(definitions))
+            }
+            NodeDefinition::Inverter(child) => {
+                NodeKind::Inverter(Box::new(Node::new(*child, next_id)))
+            }
+            NodeDefinition::Succeeder(child) => {
+                NodeKind::Succeeder(Box::new(Node::new(*child, next_id)))
+            }
+            NodeDefinition::Cooldown { seconds, child } => NodeKind::Cooldown {
+                seconds,
+                child: Box::new(Node::new(*child, next_id)),
+            },
+            NodeDefinition::Condition(condition) => NodeKind::Condition(condition),
+            NodeDefinition::Action(action) => NodeKind::Action(action),
+        };
+
+        Node { id, kind }
+    }
+
+    fn tick(&self, memory: &mut NodeMemory, context: &mut Context) -> Status {
+        match &self.kind {
+            NodeKind::Selector(children) => children
+                .iter()
+                .map(|child| child.tick(memory, context))
+                .find(|status| *status != Status::Failure)
+                .unwrap_or(Status::Failure),
+            NodeKind::Sequence(children) => children
+                .iter()
+                .map(|child| child.tick(memory, context))
+                .find(|status| *status != Status::Success)
+                .unwrap_or(Status::Success),
+            NodeKind::MemorySequence(children) => {
+                // Only resume when the sequence was still running last frame,
+                // otherwise it was interrupted and starts over.
+                let start = match memory.running.get(&self.id) {
+                    Some((child, frame)) if *frame + 1 == memory.frame => *child,
+                    _ => 0,
+                };
+
+                for (index, child) in children.iter().enumerate().skip(start) {
+                    match child.tick(memory, context) {
+                        Status::Success => continue,
+                        Status::Running => {
+                            memory.running.insert(self.id, (index, memory.frame));
+                            return Status::Running;
+                        }
+                        Status::Failure => {
+                            memory.running.remove(&self.id);
+                            return Status::Failure;
+                        }
+                    }
+                }
+
+                memory.running.remove(&self.id);
+                Status::Success
+            }
+            NodeKind::Inverter(child) => match child.tick(memory, context) {
+                Status::Success => Status::Failure,
+                Status::Failure => Status::Success,
+                Status::Running => Status::Running,
+            },
+            NodeKind::Succeeder(child) => match child.tick(memory, context) {
+                Status::Running => Status::Running,
+                _ => Status::Success,
+            },
+            NodeKind::Cooldown { seconds, child } => {
+                let ready_at = memory.ready_at.get(&self.id).copied().unwrap_or(0.0);
+                if context.now < ready_at {
+                    return Status::Failure;
+                }
+
+                let status = child.tick(memory, context);
+                if status == Status::Success {
+                    memory.ready_at.insert(self.id, context.now + seconds);
+                }
+
+                status
+            }
+            NodeKind::Condition(condition) => match condition.check(context) {
+                true => Status::Success,
+                false => Status::Failure,
+            },
+            NodeKind::Action(action) => action.run(context),
+        }
+    }
+}
+
+#[derive(Asset, TypePath)]
+pub struct BehaviourTree {
+    root: Node,
+}
+
+impl BehaviourTree {
+    pub fn tick(&self, memory: &mut NodeMemory, context: &mut Context) -> Status {
+        memory.frame += 1;
+        self.root.tick(memory, context)
+    }
+}
+
+/// What the nodes of a tree remember between frames for one character.
+#[derive(Default)]
+pub struct NodeMemory {
+    frame: u32,
+    running: HashMap<usize, (usize, u32)>,
+    ready_at: HashMap<usize, f32>,
+}
+
+#[derive(Default)]
+pub struct BehaviourTreeLoader;
+
+#[derive(Debug, Error)]
+pub enum BehaviourTreeLoaderError {
+    #[error("could not read the behaviour tree: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse the behaviour tree: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for BehaviourTreeLoader {
+    type Asset = BehaviourTree;
+    type Settings = ();
+    type Error = BehaviourTreeLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<BehaviourTree, BehaviourTreeLoaderError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            let definition = ron::de::from_bytes::<TreeDefinition>(&bytes)?;
+
+            Ok(BehaviourTree {
+                root: Node::new(definition.root, &mut 0),
+            })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["bt.ron"]
+    }
+}
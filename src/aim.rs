@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    camera_controller::CameraController, local_player::LocalPlayer, player::PlayerState,
+    schedule::InGameSet,
+};
+
+const AIM_RANGE: f32 = 100.0;
+const HIGHLIGHT_EMISSIVE: Color = Color::rgb(0.4, 0.35, 0.1);
+
+/// What a player's crosshair currently rests on while aiming.
+#[derive(Component, Default, PartialEq)]
+pub struct AimTarget(pub Option<Entity>);
+
+/// Marks a mesh of an aim target, drawn with a highlighted copy of
+/// `original` so other meshes sharing the material are left alone.
+#[derive(Component)]
+pub struct Highlighted {
+    target: Entity,
+    original: Handle<StandardMaterial>,
+}
+
+pub struct AimPlugin;
+
+impl Plugin for AimPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (update_aim_target, highlight_aim_target)
+                .chain()
+                .in_set(InGameSet::EntityUpdates),
+        );
+    }
+}
+
+fn update_aim_target(
+    mut player_query: Query<(Entity, &PlayerState, &LocalPlayer, &mut AimTarget)>,
+    camera_query: Query<(&LocalPlayer, &Transform), With<CameraController>>,
+    rapier_context: Res<RapierContext>,
+) {
+    for (player, player_state, local_player, mut aim_target) in player_query.iter_mut() {
+        let camera_transform = camera_query
+            .iter()
+            .find(|(owner, _)| *owner == local_player)
+            .map(|(_, transform)| transform);
+
+        let target = match (player_state, camera_transform) {
+            (PlayerState::Aiming, Some(camera_transform)) => rapier_context
+                .cast_ray(
+                    camera_transform.translation,
+                    *camera_transform.forward(),
+                    AIM_RANGE,
+                    true,
+                    QueryFilter::default()
+                        .exclude_collider(player)
+                        .exclude_sensors(),
+                )
+                .map(|(entity, _)| entity),
+            _ => None,
+        };
+
+        aim_target.set_if_neq(AimTarget(target));
+    }
+}
+
+fn highlight_aim_target(
+    mut commands: Commands,
+    aim_target_query: Query<Ref<AimTarget>>,
+    mut highlighted_query: Query<(Entity, &Highlighted, &mut Handle<StandardMaterial>)>,
+    mut material_query: Query<&mut Handle<StandardMaterial>, Without<Highlighted>>,
+    children_query: Query<&Children>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if !aim_target_query
+        .iter()
+        .any(|aim_target| aim_target.is_changed())
+    {
+        return;
+    }
+
+    let mut targets = aim_target_query
+        .iter()
+        .filter_map(|aim_target| aim_target.0)
+        .collect::<Vec<_>>();
+    targets.sort();
+    targets.dedup();
+
+    for (entity, highlighted, mut material_handle) in highlighted_query.iter_mut() {
+        if targets.contains(&highlighted.target) {
+            continue;
+        }
+
+        *material_handle = highlighted.original.clone();
+        commands.entity(entity).remove::<Highlighted>();
+    }
+
+    // Characters and other glTF scenes keep their meshes in child entities.
+    for target in targets {
+        let meshes = std::iter::once(target).chain(children_query.iter_descendants(target));
+
+        for entity in meshes {
+            let Ok(mut material_handle) = material_query.get_mut(entity) else {
+                continue;
+            };
+
+            let Some(mut material) = materials.get(&*material_handle).cloned() else {
+                continue;
+            };
+
+            material.emissive = HIGHLIGHT_EMISSIVE;
+            commands.entity(entity).insert(Highlighted {
+                target,
+                original: material_handle.clone(),
+            });
+            *material_handle = materials.add(material);
+        }
+    }
+}
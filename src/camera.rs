@@ -5,6 +5,9 @@ use bevy::{
 
 use crate::{
     camera_controller::{CameraController, CameraControllerDescriptor},
+    camera_mode::{CameraRig, FreeFlyCamera},
+    camera_shake::{CameraShake, CameraShakeDescriptor},
+    local_player::{LocalPlayer, LocalPlayers},
     state::GameState,
 };
 
@@ -12,15 +15,27 @@ pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn_camera)
+        app.add_systems(Startup, spawn_cameras)
             .add_systems(OnEnter(GameState::InGame), lock_cursor)
             .add_systems(OnExit(GameState::InGame), unlock_cursor);
     }
 }
 
-fn spawn_camera(mut commands: Commands) {
+fn spawn_cameras(mut commands: Commands, local_players: Res<LocalPlayers>) {
+    for index in 0..local_players.count {
+        spawn_camera(&mut commands, LocalPlayer(index));
+    }
+}
+
+fn spawn_camera(commands: &mut Commands, local_player: LocalPlayer) {
     let camera = (
-        Camera3dBundle::default(),
+        Camera3dBundle {
+            camera: Camera {
+                order: local_player.0 as isize,
+                ..default()
+            },
+            ..default()
+        },
         CameraController::new(CameraControllerDescriptor {
             min_radius: 4.5,
             max_radius: 12.5,
@@ -29,8 +44,21 @@ fn spawn_camera(mut commands: Commands) {
             mouse_sensitivity: 0.5,
             zoom_sensitivity: 0.5,
             movement_smoothness: 0.05,
+            shoulder_swap_smoothness: 0.1,
+            auto_follow_delay: Some(1.5),
+            auto_follow_speed: 0.4,
+            handoff_duration: 0.8,
+        }),
+        CameraRig::new(0.6),
+        FreeFlyCamera::new(6.0, 0.5),
+        CameraShake::new(CameraShakeDescriptor {
+            trauma_decay: 1.5,
+            max_angle: 0.05,
+            max_offset: 0.1,
+            frequency: 15.0,
         }),
-        Name::new("Main camera"),
+        local_player,
+        Name::new(format!("Player {} camera", local_player.0 + 1)),
     );
 
     commands.spawn(camera);
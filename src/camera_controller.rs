@@ -1,6 +1,12 @@
-use bevy::{input::mouse::*, prelude::*};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
 
-use crate::state::GameState;
+use crate::{
+    camera_mode::CameraPose,
+    local_player::{LocalPlayer, PlayerAction, PlayerInput},
+    schedule::CameraSet,
+    state::GameState,
+};
 
 pub struct CameraControllerDescriptor {
     pub min_radius: f32,
@@ -10,6 +16,13 @@ pub struct CameraControllerDescriptor {
     pub mouse_sensitivity: f32,
     pub zoom_sensitivity: f32,
     pub movement_smoothness: f32,
+    pub shoulder_swap_smoothness: f32,
+    /// Seconds without camera input before the camera starts trailing the
+    /// target's movement, `None` disables the auto follow.
+    pub auto_follow_delay: Option<f32>,
+    pub auto_follow_speed: f32,
+    /// Seconds the focus takes to travel over when the target changes.
+    pub handoff_duration: f32,
 }
 
 #[derive(Component, Reflect)]
@@ -18,6 +31,7 @@ pub struct CameraController {
     pitch: f32,
     radius: f32,
     radius_target: f32,
+    radius_before_aiming: f32,
     min_radius: f32,
     max_radius: f32,
     max_offset: Vec2,
@@ -26,6 +40,25 @@ pub struct CameraController {
     mouse_sensitivity: f32,
     zoom_sensitivity: f32,
     movement_smoothness: f32,
+    shoulder: f32,
+    shoulder_target: f32,
+    shoulder_swap_smoothness: f32,
+    manual_shoulder_time: f32,
+    framing_target: Option<Vec3>,
+    pose: CameraPose,
+    auto_follow_delay: Option<f32>,
+    auto_follow_speed: f32,
+    idle_time: f32,
+    previous_target_position: Vec3,
+    target: Option<Entity>,
+    handoff_duration: f32,
+    handoff: Option<TargetHandoff>,
+}
+
+#[derive(Reflect)]
+struct TargetHandoff {
+    from: Vec3,
+    elapsed: f32,
 }
 
 impl CameraController {
@@ -39,6 +72,7 @@ impl CameraController {
             pitch: 0.0,
             radius: initial_radius,
             radius_target: initial_radius,
+            radius_before_aiming: initial_radius,
             min_radius: descriptor.min_radius,
             max_radius: descriptor.max_radius,
             max_offset: descriptor.max_offset,
@@ -46,12 +80,66 @@ impl CameraController {
             mouse_sensitivity: descriptor.mouse_sensitivity,
             zoom_sensitivity: descriptor.zoom_sensitivity,
             movement_smoothness: descriptor.movement_smoothness,
+            shoulder: 1.0,
+            shoulder_target: 1.0,
+            shoulder_swap_smoothness: descriptor.shoulder_swap_smoothness,
+            manual_shoulder_time: 0.0,
+            framing_target: None,
+            pose: CameraPose::default(),
+            auto_follow_delay: descriptor.auto_follow_delay,
+            auto_follow_speed: descriptor.auto_follow_speed,
+            idle_time: 0.0,
+            previous_target_position: Vec3::ZERO,
+            target: None,
+            handoff_duration: descriptor.handoff_duration,
+            handoff: None,
         }
     }
+
+    fn offset(&self) -> Vec2 {
+        let percentage = (self.radius - self.min_radius) / (self.max_radius - self.min_radius);
+
+        let offset = (self.max_offset * percentage) + (self.min_offset * (1.0 - percentage));
+
+        Vec2::new(offset.x * self.shoulder, offset.y)
+    }
+
+    fn swap_shoulder(&mut self) {
+        self.shoulder_target = -self.shoulder_target;
+    }
+
+    /// Keeps a second point in frame alongside the target, turning the camera
+    /// towards it and shifting the focus in between both.
+    pub fn set_framing_target(&mut self, framing_target: Option<Vec3>) {
+        self.framing_target = framing_target;
+    }
+
+    /// The orbit pose computed on the last update, the active camera mode
+    /// decides whether it ends up being rendered.
+    pub fn pose(&self) -> CameraPose {
+        self.pose
+    }
+
+    /// The `CameraTarget` entity currently followed, if any.
+    pub fn target(&self) -> Option<Entity> {
+        self.target
+    }
+
+    pub fn focus(&self) -> Vec3 {
+        self.focus
+    }
+
+    pub fn add_recoil(&mut self, recoil: f32) {
+        self.pitch += recoil;
+    }
 }
 
+/// Something the camera can follow, the camera follows the target with the
+/// highest priority.
 #[derive(Component)]
-pub struct CameraTarget;
+pub struct CameraTarget {
+    pub priority: i32,
+}
 
 pub struct CameraControllerPlugin;
 
@@ -60,126 +148,323 @@ impl Plugin for CameraControllerPlugin {
         app.add_systems(
             Update,
             (
+                select_camera_target,
                 orbit_camera,
-                zoom_camera_with_scroll,
+                zoom_camera,
+                swap_shoulder,
+                avoid_shoulder_occlusion,
+                auto_follow_target,
                 sync_camera_with_target,
             )
-                .chain(),
+                .chain()
+                .in_set(CameraSet::Controller),
         )
         .add_systems(
             Update,
-            focus_camera_with_right_mouse.run_if(in_state(GameState::InGame)),
+            focus_camera_when_aiming
+                .run_if(in_state(GameState::InGame))
+                .in_set(CameraSet::Controller),
         )
         .register_type::<CameraController>();
     }
 }
 
+/// Picks the highest priority target among the shared targets and the ones
+/// belonging to the camera's own local player.
+fn select_camera_target(
+    mut camera_query: Query<(&LocalPlayer, &mut CameraController)>,
+    target_query: Query<(Entity, &CameraTarget, Option<&LocalPlayer>)>,
+) {
+    for (local_player, mut camera_controller) in camera_query.iter_mut() {
+        let target = target_query
+            .iter()
+            .filter(|(_, _, owner)| owner.is_none_or(|owner| owner == local_player))
+            .max_by_key(|(_, camera_target, _)| camera_target.priority)
+            .map(|(entity, _, _)| entity);
+
+        if camera_controller.target == target {
+            continue;
+        }
+
+        // The very first target is picked up as usual, later ones hand the
+        // focus over from wherever it currently is.
+        if camera_controller.target.is_some() {
+            camera_controller.handoff = Some(TargetHandoff {
+                from: camera_controller.focus,
+                elapsed: 0.0,
+            });
+        }
+
+        camera_controller.target = target;
+    }
+}
+
+fn find_input<'a>(
+    input_query: &'a Query<(&LocalPlayer, &PlayerInput)>,
+    local_player: &LocalPlayer,
+) -> Option<&'a PlayerInput> {
+    input_query
+        .iter()
+        .find(|(owner, _)| *owner == local_player)
+        .map(|(_, player_input)| player_input)
+}
+
 fn orbit_camera(
-    window_query: Query<&Window>,
-    mut mouse_motion_event: EventReader<MouseMotion>,
-    mut camera_query: Query<&mut CameraController>,
+    mut camera_query: Query<(&LocalPlayer, &mut CameraController)>,
+    input_query: Query<(&LocalPlayer, &PlayerInput)>,
     state: Res<State<GameState>>,
+    time: Res<Time>,
 ) {
-    if state.get() == &GameState::InGame {
-        let mut camera_controller = camera_query
-            .get_single_mut()
-            .expect("There should be one and only one camera with a CameraController");
+    if state.get() != &GameState::InGame {
+        return;
+    }
 
-        let window = window_query.get_single().unwrap();
+    for (local_player, mut camera_controller) in camera_query.iter_mut() {
+        let look = find_input(&input_query, local_player).map_or(Vec2::ZERO, |input| input.look);
 
-        let mouse_delta = mouse_motion_event
-            .read()
-            .map(|event| event.delta)
-            .sum::<Vec2>();
+        match look == Vec2::ZERO {
+            true => camera_controller.idle_time += time.delta_seconds(),
+            false => camera_controller.idle_time = 0.0,
+        }
 
         let Vec2 {
             x: delta_x,
             y: delta_y,
-        } = mouse_delta / window.width()
-            * camera_controller.mouse_sensitivity
-            * std::f32::consts::PI
-            * 2.0;
+        } = look * camera_controller.mouse_sensitivity * std::f32::consts::PI * 2.0;
 
         camera_controller.yawn -= delta_x;
         camera_controller.pitch -= delta_y;
     }
-    mouse_motion_event.clear();
 }
 
-fn zoom_camera_with_scroll(
-    mut scroll_events: EventReader<MouseWheel>,
-    mut camera_query: Query<&mut CameraController>,
+fn zoom_camera(
+    mut camera_query: Query<(&LocalPlayer, &mut CameraController)>,
+    input_query: Query<(&LocalPlayer, &PlayerInput)>,
     state: Res<State<GameState>>,
 ) {
-    if state.get() == &GameState::InGame {
-        let mut camera_controller = camera_query
-            .get_single_mut()
-            .expect("There should be one and only one camera with a CameraController");
+    if state.get() != &GameState::InGame {
+        return;
+    }
 
-        let scroll_delta = scroll_events.read().map(|event| -event.y).sum::<f32>();
+    for (local_player, mut camera_controller) in camera_query.iter_mut() {
+        let zoom = find_input(&input_query, local_player).map_or(0.0, |input| input.zoom);
 
-        camera_controller.radius_target += scroll_delta * camera_controller.zoom_sensitivity;
+        camera_controller.radius_target += zoom * camera_controller.zoom_sensitivity;
         camera_controller.radius_target = camera_controller
             .radius_target
             .clamp(camera_controller.min_radius, camera_controller.max_radius);
     }
-    scroll_events.clear();
 }
 
-fn focus_camera_with_right_mouse(
-    mouse_input: Res<ButtonInput<MouseButton>>,
+fn focus_camera_when_aiming(
+    mut camera_query: Query<(&LocalPlayer, &mut CameraController)>,
+    input_query: Query<(&LocalPlayer, &PlayerInput)>,
+) {
+    for (local_player, mut camera_controller) in camera_query.iter_mut() {
+        let Some(player_input) = find_input(&input_query, local_player) else {
+            continue;
+        };
+
+        if player_input.actions.just_pressed(PlayerAction::Aim) {
+            camera_controller.radius_before_aiming = camera_controller.radius;
+            camera_controller.radius_target = camera_controller.min_radius;
+        } else if player_input.actions.just_released(PlayerAction::Aim) {
+            camera_controller.radius_target = camera_controller.radius_before_aiming;
+        }
+    }
+}
+
+fn swap_shoulder(
+    mut camera_query: Query<(&LocalPlayer, &mut CameraController)>,
+    input_query: Query<(&LocalPlayer, &PlayerInput)>,
+    state: Res<State<GameState>>,
+) {
+    if state.get() != &GameState::InGame {
+        return;
+    }
+
+    for (local_player, mut camera_controller) in camera_query.iter_mut() {
+        let swap = find_input(&input_query, local_player)
+            .is_some_and(|input| input.actions.just_pressed(PlayerAction::SwapShoulder));
+
+        if swap {
+            camera_controller.swap_shoulder();
+            camera_controller.manual_shoulder_time = MANUAL_SHOULDER_HOLD;
+        }
+    }
+}
+
+const SHOULDER_CLEARANCE: f32 = 0.3;
+/// Seconds a shoulder picked by hand is kept before the occlusion check may
+/// swap it again.
+const MANUAL_SHOULDER_HOLD: f32 = 2.0;
+
+fn avoid_shoulder_occlusion(
     mut camera_query: Query<&mut CameraController>,
-    mut previous_radius: Local<f32>,
+    character_query: Query<(), With<KinematicCharacterController>>,
+    rapier_context: Res<RapierContext>,
+    time: Res<Time>,
+) {
+    let is_not_character = |entity| !character_query.contains(entity);
+    let filter = QueryFilter::default()
+        .exclude_sensors()
+        .predicate(&is_not_character);
+
+    for mut camera_controller in camera_query.iter_mut() {
+        if camera_controller.manual_shoulder_time > 0.0 {
+            camera_controller.manual_shoulder_time -= time.delta_seconds();
+            continue;
+        }
+
+        let side_distance = camera_controller.offset().x.abs() + SHOULDER_CLEARANCE;
+        let right = Quat::from_rotation_y(camera_controller.yawn) * Vec3::X;
+
+        let is_occluded = |side: f32| {
+            rapier_context
+                .cast_ray(
+                    camera_controller.focus,
+                    right * side,
+                    side_distance,
+                    true,
+                    filter,
+                )
+                .is_some()
+        };
+
+        let side = camera_controller.shoulder_target;
+        if is_occluded(side) && !is_occluded(-side) {
+            camera_controller.swap_shoulder();
+        }
+    }
+}
+
+/// Only follow movement that is not heading back towards the camera,
+/// otherwise walking towards it would spin the camera around.
+const AUTO_FOLLOW_MAX_ANGLE: f32 = std::f32::consts::FRAC_PI_4 * 3.0;
+
+fn auto_follow_target(
+    mut camera_query: Query<(&LocalPlayer, &mut CameraController)>,
+    target_query: Query<&GlobalTransform, With<CameraTarget>>,
+    input_query: Query<(&LocalPlayer, &PlayerInput)>,
+    time: Res<Time>,
 ) {
-    let mut camera_controller = camera_query
-        .get_single_mut()
-        .expect("There should be one and only one camera with a CameraController");
+    for (local_player, mut camera_controller) in camera_query.iter_mut() {
+        let Some(target_transform) = camera_controller
+            .target
+            .and_then(|target| target_query.get(target).ok())
+        else {
+            continue;
+        };
+
+        let target_position = target_transform.translation();
+        let mut velocity = (target_position - camera_controller.previous_target_position)
+            / time.delta_seconds().max(f32::EPSILON);
+        velocity.y = 0.0;
+        camera_controller.previous_target_position = target_position;
+
+        let Some(auto_follow_delay) = camera_controller.auto_follow_delay else {
+            continue;
+        };
+
+        let aiming = find_input(&input_query, local_player)
+            .is_some_and(|input| input.actions.pressed(PlayerAction::Aim));
+
+        if camera_controller.idle_time < auto_follow_delay
+            || camera_controller.framing_target.is_some()
+            || aiming
+            || velocity.length_squared() < 0.01
+        {
+            continue;
+        }
+
+        let follow_yawn = (-velocity.x).atan2(-velocity.z);
+        let yawn_delta = angle_difference(camera_controller.yawn, follow_yawn);
 
-    if mouse_input.just_pressed(MouseButton::Right) {
-        *previous_radius = camera_controller.radius;
-        camera_controller.radius_target = camera_controller.min_radius;
-    } else if mouse_input.just_released(MouseButton::Right) {
-        camera_controller.radius_target = *previous_radius;
+        if yawn_delta.abs() > AUTO_FOLLOW_MAX_ANGLE {
+            continue;
+        }
+
+        let follow_rate =
+            (camera_controller.auto_follow_speed * velocity.length() * time.delta_seconds())
+                .min(1.0);
+        camera_controller.yawn += yawn_delta * follow_rate;
     }
 }
 
+/// Signed shortest rotation from one angle to another, in `[-PI, PI)`.
+fn angle_difference(from: f32, to: f32) -> f32 {
+    (to - from + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU) - std::f32::consts::PI
+}
+
+const FRAMING_YAWN_SMOOTHNESS: f32 = 0.1;
+const FRAMING_FOCUS_WEIGHT: f32 = 0.35;
+
 fn sync_camera_with_target(
-    mut camera_query: Query<(&mut Transform, &mut CameraController)>,
+    mut camera_query: Query<&mut CameraController>,
     target_query: Query<&GlobalTransform, (With<CameraTarget>, Without<CameraController>)>,
+    time: Res<Time>,
 ) {
-    let (mut camera_transform, mut camera_controller) = camera_query
-        .get_single_mut()
-        .expect("There should be one and only one camera with a CameraController");
+    for mut camera_controller in camera_query.iter_mut() {
+        // Without a target the camera keeps orbiting around its last focus.
+        let target_position = camera_controller
+            .target
+            .and_then(|target| target_query.get(target).ok())
+            .map_or(camera_controller.focus, |transform| transform.translation());
+
+        let mut focus_target = target_position;
+
+        if let Some(framing_target) = camera_controller.framing_target {
+            let to_framing_target = framing_target - target_position;
+            let framing_yawn = (-to_framing_target.x).atan2(-to_framing_target.z);
 
-    let target_transform = target_query
-        .get_single()
-        .expect("There should be one and only one CameraTarget");
+            let yawn_delta = angle_difference(camera_controller.yawn, framing_yawn);
+            camera_controller.yawn += yawn_delta * FRAMING_YAWN_SMOOTHNESS;
 
-    let mut rotation = Quat::from_rotation_y(camera_controller.yawn);
-    rotation *= Quat::from_rotation_x(camera_controller.pitch);
+            focus_target = focus_target.lerp(framing_target, FRAMING_FOCUS_WEIGHT);
+        }
+
+        let mut rotation = Quat::from_rotation_y(camera_controller.yawn);
+        rotation *= Quat::from_rotation_x(camera_controller.pitch);
 
-    camera_controller.radius = camera_controller.radius_target
-        - (camera_controller.radius_target - camera_controller.radius) * 0.5;
+        camera_controller.radius = camera_controller.radius_target
+            - (camera_controller.radius_target - camera_controller.radius) * 0.5;
 
-    let offset = {
-        let percentage = (camera_controller.radius - camera_controller.min_radius)
-            / (camera_controller.max_radius - camera_controller.min_radius);
+        camera_controller.shoulder += (camera_controller.shoulder_target
+            - camera_controller.shoulder)
+            * camera_controller.shoulder_swap_smoothness;
 
-        (camera_controller.max_offset * percentage)
-            + (camera_controller.min_offset * (1.0 - percentage))
-    };
+        let offset = camera_controller.offset();
 
-    let right = camera_transform.rotation * Vec3::X * offset.x;
-    let up = camera_transform.rotation * Vec3::Y * offset.y;
-    let pan_translation = right + up;
+        let right = rotation * Vec3::X * offset.x;
+        let up = rotation * Vec3::Y * offset.y;
+        let pan_translation = right + up;
 
-    camera_transform.rotation = rotation;
+        let handoff_duration = camera_controller.handoff_duration;
+        camera_controller.focus = match camera_controller.handoff.as_mut() {
+            Some(handoff) => {
+                handoff.elapsed += time.delta_seconds();
 
-    camera_controller.focus = camera_controller.focus
-        + (target_transform.translation() - camera_controller.focus)
-            * camera_controller.movement_smoothness;
+                let t = (handoff.elapsed / handoff_duration).min(1.0);
+                handoff.from.lerp(focus_target, t * t * (3.0 - 2.0 * t))
+            }
+            None => {
+                camera_controller.focus
+                    + (focus_target - camera_controller.focus)
+                        * camera_controller.movement_smoothness
+            }
+        };
 
-    camera_transform.translation = camera_controller.focus
-        + camera_transform.rotation * Vec3::new(0.0, 0.0, camera_controller.radius)
-        + pan_translation;
+        if camera_controller
+            .handoff
+            .as_ref()
+            .is_some_and(|handoff| handoff.elapsed >= handoff_duration)
+        {
+            camera_controller.handoff = None;
+        }
+
+        camera_controller.pose.rotation = rotation;
+        camera_controller.pose.translation = camera_controller.focus
+            + rotation * Vec3::new(0.0, 0.0, camera_controller.radius)
+            + pan_translation;
+    }
 }
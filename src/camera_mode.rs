@@ -0,0 +1,392 @@
+use bevy::{input::mouse::*, prelude::*};
+
+use crate::{
+    camera_controller::{CameraController, CameraTarget},
+    schedule::CameraSet,
+    state::GameState,
+};
+
+const ORBIT_FOV: f32 = std::f32::consts::FRAC_PI_4;
+const FIRST_PERSON_FOV: f32 = std::f32::consts::FRAC_PI_3;
+const FIXED_FOV: f32 = std::f32::consts::FRAC_PI_4;
+const TOP_DOWN_FOV: f32 = std::f32::consts::FRAC_PI_6;
+const FREE_FLY_FOV: f32 = std::f32::consts::FRAC_PI_3;
+
+const FIRST_PERSON_EYE_OFFSET: Vec3 = Vec3::new(0.0, 0.6, 0.0);
+const FIXED_POSITION: Vec3 = Vec3::new(-3.5, 4.0, 3.5);
+const TOP_DOWN_HEIGHT: f32 = 15.0;
+
+/// Keyboard-only controls of the debug cameras. They aren't player actions,
+/// so they live here instead of in the per-player bindings.
+struct DebugKeys {
+    toggle_free_fly: KeyCode,
+    cycle_camera_mode: KeyCode,
+    forward: KeyCode,
+    back: KeyCode,
+    right: KeyCode,
+    left: KeyCode,
+    up: KeyCode,
+    down: KeyCode,
+}
+
+const DEBUG_KEYS: DebugKeys = DebugKeys {
+    toggle_free_fly: KeyCode::F1,
+    cycle_camera_mode: KeyCode::F5,
+    forward: KeyCode::KeyW,
+    back: KeyCode::KeyS,
+    right: KeyCode::KeyD,
+    left: KeyCode::KeyA,
+    up: KeyCode::KeyE,
+    down: KeyCode::KeyQ,
+};
+
+#[derive(Clone, Copy, Debug, Reflect)]
+pub struct CameraPose {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub fov: f32,
+}
+
+impl CameraPose {
+    fn lerp(&self, other: &CameraPose, t: f32) -> CameraPose {
+        CameraPose {
+            translation: self.translation.lerp(other.translation, t),
+            rotation: self.rotation.slerp(other.rotation, t),
+            fov: self.fov + (other.fov - self.fov) * t,
+        }
+    }
+}
+
+impl Default for CameraPose {
+    fn default() -> Self {
+        CameraPose {
+            translation: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            fov: ORBIT_FOV,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Reflect)]
+pub enum CameraMode {
+    Orbit,
+    FirstPerson,
+    Fixed(Vec3),
+    TopDown,
+    FreeFly,
+    Scripted,
+}
+
+impl CameraMode {
+    fn next(&self) -> CameraMode {
+        match self {
+            CameraMode::Orbit => CameraMode::FirstPerson,
+            CameraMode::FirstPerson => CameraMode::Fixed(FIXED_POSITION),
+            CameraMode::Fixed(_) => CameraMode::TopDown,
+            CameraMode::TopDown | CameraMode::FreeFly | CameraMode::Scripted => CameraMode::Orbit,
+        }
+    }
+}
+
+/// Picks the pose of the active `CameraMode` and blends from the previous
+/// pose whenever the mode changes.
+#[derive(Component, Reflect)]
+pub struct CameraRig {
+    mode: CameraMode,
+    blend_duration: f32,
+    blend_from: Option<CameraPose>,
+    blend_elapsed: f32,
+    pose: CameraPose,
+    mode_before_free_fly: CameraMode,
+    scripted_pose: CameraPose,
+    mode_before_scripted: CameraMode,
+}
+
+impl CameraRig {
+    pub fn new(blend_duration: f32) -> CameraRig {
+        CameraRig {
+            mode: CameraMode::Orbit,
+            blend_duration,
+            blend_from: None,
+            blend_elapsed: 0.0,
+            pose: CameraPose::default(),
+            mode_before_free_fly: CameraMode::Orbit,
+            scripted_pose: CameraPose::default(),
+            mode_before_scripted: CameraMode::Orbit,
+        }
+    }
+
+    pub fn set_mode(&mut self, mode: CameraMode) {
+        if self.mode == mode {
+            return;
+        }
+
+        self.mode = mode;
+        self.blend_from = Some(self.pose);
+        self.blend_elapsed = 0.0;
+    }
+
+    /// Hands the camera over to a scripted pose, like a cutscene rail, the
+    /// first call blends into it from the current mode.
+    pub fn play_scripted(&mut self, pose: CameraPose) {
+        if self.mode != CameraMode::Scripted {
+            self.mode_before_scripted = self.mode;
+            self.set_mode(CameraMode::Scripted);
+        }
+
+        self.scripted_pose = pose;
+    }
+
+    pub fn stop_scripted(&mut self) {
+        if self.mode == CameraMode::Scripted {
+            let mode = self.mode_before_scripted;
+            self.set_mode(mode);
+        }
+    }
+}
+
+#[derive(Component, Reflect)]
+pub struct FreeFlyCamera {
+    translation: Vec3,
+    yawn: f32,
+    pitch: f32,
+    speed: f32,
+    mouse_sensitivity: f32,
+}
+
+impl FreeFlyCamera {
+    pub fn new(speed: f32, mouse_sensitivity: f32) -> FreeFlyCamera {
+        FreeFlyCamera {
+            translation: Vec3::ZERO,
+            yawn: 0.0,
+            pitch: 0.0,
+            speed,
+            mouse_sensitivity,
+        }
+    }
+
+    fn pose(&self) -> CameraPose {
+        CameraPose {
+            translation: self.translation,
+            rotation: Quat::from_rotation_y(self.yawn) * Quat::from_rotation_x(self.pitch),
+            fov: FREE_FLY_FOV,
+        }
+    }
+}
+
+pub struct CameraModePlugin;
+
+impl Plugin for CameraModePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                toggle_free_fly_camera.run_if(in_state(GameState::InGame)),
+                cycle_camera_mode.run_if(in_state(GameState::InGame)),
+                fly_free_camera.run_if(in_state(GameState::InGame)),
+                update_camera_rig,
+            )
+                .chain()
+                .in_set(CameraSet::Rig),
+        )
+        .register_type::<CameraRig>()
+        .register_type::<FreeFlyCamera>();
+    }
+}
+
+/// Run condition for everything that has to stand still while the debug
+/// free-fly camera is in control, like the orbit controller or player input.
+pub fn free_fly_active(camera_query: Query<&CameraRig>) -> bool {
+    camera_query
+        .iter()
+        .any(|camera_rig| camera_rig.mode == CameraMode::FreeFly)
+}
+
+fn toggle_free_fly_camera(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut camera_query: Query<(&mut CameraRig, &mut FreeFlyCamera)>,
+) {
+    if !keys.just_pressed(DEBUG_KEYS.toggle_free_fly) {
+        return;
+    }
+
+    for (mut camera_rig, mut free_fly_camera) in camera_query.iter_mut() {
+        if camera_rig.mode == CameraMode::Scripted {
+            continue;
+        }
+
+        if camera_rig.mode == CameraMode::FreeFly {
+            let mode = camera_rig.mode_before_free_fly;
+            camera_rig.set_mode(mode);
+            continue;
+        }
+
+        // Start flying from wherever the camera currently is.
+        let (yawn, pitch, _) = camera_rig.pose.rotation.to_euler(EulerRot::YXZ);
+
+        free_fly_camera.translation = camera_rig.pose.translation;
+        free_fly_camera.yawn = yawn;
+        free_fly_camera.pitch = pitch;
+
+        camera_rig.mode_before_free_fly = camera_rig.mode;
+        camera_rig.set_mode(CameraMode::FreeFly);
+    }
+}
+
+fn cycle_camera_mode(keys: Res<ButtonInput<KeyCode>>, mut camera_query: Query<&mut CameraRig>) {
+    if !keys.just_pressed(DEBUG_KEYS.cycle_camera_mode) {
+        return;
+    }
+
+    for mut camera_rig in camera_query.iter_mut() {
+        if matches!(camera_rig.mode, CameraMode::FreeFly | CameraMode::Scripted) {
+            continue;
+        }
+
+        let mode = camera_rig.mode.next();
+        camera_rig.set_mode(mode);
+    }
+}
+
+fn fly_free_camera(
+    window_query: Query<&Window>,
+    mut mouse_motion_event: EventReader<MouseMotion>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut camera_query: Query<(&CameraRig, &mut FreeFlyCamera)>,
+    time: Res<Time>,
+) {
+    let mouse_delta = mouse_motion_event
+        .read()
+        .map(|event| event.delta)
+        .sum::<Vec2>();
+
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+
+    for (camera_rig, mut free_fly_camera) in camera_query.iter_mut() {
+        if camera_rig.mode != CameraMode::FreeFly {
+            continue;
+        }
+
+        let Vec2 {
+            x: delta_x,
+            y: delta_y,
+        } = mouse_delta / window.width()
+            * free_fly_camera.mouse_sensitivity
+            * std::f32::consts::PI
+            * 2.0;
+
+        free_fly_camera.yawn -= delta_x;
+        free_fly_camera.pitch -= delta_y;
+
+        let rotation = free_fly_camera.pose().rotation;
+        let mut direction = Vec3::ZERO;
+
+        if keys.pressed(DEBUG_KEYS.forward) {
+            direction += rotation * Vec3::NEG_Z;
+        }
+
+        if keys.pressed(DEBUG_KEYS.back) {
+            direction += rotation * Vec3::Z;
+        }
+
+        if keys.pressed(DEBUG_KEYS.right) {
+            direction += rotation * Vec3::X;
+        }
+
+        if keys.pressed(DEBUG_KEYS.left) {
+            direction += rotation * Vec3::NEG_X;
+        }
+
+        if keys.pressed(DEBUG_KEYS.up) {
+            direction += Vec3::Y;
+        }
+
+        if keys.pressed(DEBUG_KEYS.down) {
+            direction += Vec3::NEG_Y;
+        }
+
+        let speed = free_fly_camera.speed;
+        free_fly_camera.translation += direction.normalize_or_zero() * speed * time.delta_seconds();
+    }
+}
+
+fn update_camera_rig(
+    mut camera_query: Query<(
+        &mut Transform,
+        &mut Projection,
+        &mut CameraRig,
+        &CameraController,
+        Option<&FreeFlyCamera>,
+    )>,
+    target_query: Query<&GlobalTransform, (With<CameraTarget>, Without<CameraRig>)>,
+    time: Res<Time>,
+) {
+    for (mut transform, mut projection, mut camera_rig, camera_controller, free_fly_camera) in
+        camera_query.iter_mut()
+    {
+        let orbit_pose = camera_controller.pose();
+        let target_position = camera_controller
+            .target()
+            .and_then(|target| target_query.get(target).ok())
+            .map_or(camera_controller.focus(), |transform| {
+                transform.translation()
+            });
+
+        let desired_pose = match camera_rig.mode {
+            CameraMode::Orbit => CameraPose {
+                fov: ORBIT_FOV,
+                ..orbit_pose
+            },
+            CameraMode::FirstPerson => CameraPose {
+                translation: target_position + FIRST_PERSON_EYE_OFFSET,
+                rotation: orbit_pose.rotation,
+                fov: FIRST_PERSON_FOV,
+            },
+            CameraMode::Fixed(position) => CameraPose {
+                translation: position,
+                rotation: Transform::from_translation(position)
+                    .looking_at(target_position, Vec3::Y)
+                    .rotation,
+                fov: FIXED_FOV,
+            },
+            CameraMode::TopDown => {
+                let position = target_position + Vec3::Y * TOP_DOWN_HEIGHT;
+                let up = Quat::from_rotation_y(orbit_pose.rotation.to_euler(EulerRot::YXZ).0)
+                    * Vec3::NEG_Z;
+
+                CameraPose {
+                    translation: position,
+                    rotation: Transform::from_translation(position)
+                        .looking_at(target_position, up)
+                        .rotation,
+                    fov: TOP_DOWN_FOV,
+                }
+            }
+            CameraMode::FreeFly => free_fly_camera.map_or(orbit_pose, FreeFlyCamera::pose),
+            CameraMode::Scripted => camera_rig.scripted_pose,
+        };
+
+        camera_rig.pose = match camera_rig.blend_from {
+            Some(blend_from) => {
+                camera_rig.blend_elapsed += time.delta_seconds();
+
+                let t = (camera_rig.blend_elapsed / camera_rig.blend_duration).min(1.0);
+                if t >= 1.0 {
+                    camera_rig.blend_from = None;
+                }
+
+                blend_from.lerp(&desired_pose, t * t * (3.0 - 2.0 * t))
+            }
+            None => desired_pose,
+        };
+
+        transform.translation = camera_rig.pose.translation;
+        transform.rotation = camera_rig.pose.rotation;
+
+        if let Projection::Perspective(perspective) = projection.as_mut() {
+            perspective.fov = camera_rig.pose.fov;
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+
+use crate::{
+    health::{Damage, Health},
+    local_player::LocalPlayer,
+    schedule::CameraSet,
+};
+
+const DAMAGE_TRAUMA_PER_POINT: f32 = 0.02;
+
+pub struct CameraShakeDescriptor {
+    /// Trauma lost per second.
+    pub trauma_decay: f32,
+    /// Rotation in radians at full trauma.
+    pub max_angle: f32,
+    pub max_offset: f32,
+    /// How fast the shake noise changes, in samples per second.
+    pub frequency: f32,
+}
+
+#[derive(Component, Reflect)]
+pub struct CameraShake {
+    trauma: f32,
+    trauma_decay: f32,
+    max_angle: f32,
+    max_offset: f32,
+    frequency: f32,
+}
+
+impl CameraShake {
+    pub fn new(descriptor: CameraShakeDescriptor) -> CameraShake {
+        CameraShake {
+            trauma: 0.0,
+            trauma_decay: descriptor.trauma_decay,
+            max_angle: descriptor.max_angle,
+            max_offset: descriptor.max_offset,
+            frequency: descriptor.frequency,
+        }
+    }
+}
+
+/// Adds trauma to the cameras of `player`, or to every camera when `None`.
+/// The shake intensity grows with the square of the accumulated trauma,
+/// which is clamped to `1.0`.
+#[derive(Event)]
+pub struct AddTrauma {
+    pub trauma: f32,
+    pub player: Option<LocalPlayer>,
+}
+
+pub struct CameraShakePlugin;
+
+impl Plugin for CameraShakePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AddTrauma>()
+            .add_systems(
+                Update,
+                (shake_on_damage, add_trauma, shake_camera)
+                    .chain()
+                    .in_set(CameraSet::Effects),
+            )
+            .register_type::<CameraShake>();
+    }
+}
+
+/// Local players feel the damage they take, ignored hits don't shake.
+fn shake_on_damage(
+    mut damage_events: EventReader<Damage>,
+    player_query: Query<(&LocalPlayer, &Health)>,
+    mut trauma_events: EventWriter<AddTrauma>,
+) {
+    for damage in damage_events.read() {
+        let Ok((local_player, health)) = player_query.get(damage.entity) else {
+            continue;
+        };
+
+        if health.invulnerable() {
+            continue;
+        }
+
+        trauma_events.send(AddTrauma {
+            trauma: damage.amount * DAMAGE_TRAUMA_PER_POINT,
+            player: Some(*local_player),
+        });
+    }
+}
+
+fn add_trauma(
+    mut trauma_events: EventReader<AddTrauma>,
+    mut shake_query: Query<(&mut CameraShake, Option<&LocalPlayer>)>,
+) {
+    for event in trauma_events.read() {
+        for (mut camera_shake, local_player) in shake_query.iter_mut() {
+            if event.player.is_some() && event.player.as_ref() != local_player {
+                continue;
+            }
+
+            camera_shake.trauma = (camera_shake.trauma + event.trauma).min(1.0);
+        }
+    }
+}
+
+/// Layers the shake on top of the transform written by the camera controller.
+/// The controller rebuilds the transform from its own state every frame, so
+/// the shake never feeds back into the orbit.
+fn shake_camera(mut camera_query: Query<(&mut Transform, &mut CameraShake)>, time: Res<Time>) {
+    for (mut transform, mut camera_shake) in camera_query.iter_mut() {
+        camera_shake.trauma =
+            (camera_shake.trauma - camera_shake.trauma_decay * time.delta_seconds()).max(0.0);
+
+        if camera_shake.trauma == 0.0 {
+            continue;
+        }
+
+        let shake = camera_shake.trauma * camera_shake.trauma;
+        let t = time.elapsed_seconds() * camera_shake.frequency;
+
+        let yawn = camera_shake.max_angle * shake * noise(0, t);
+        let pitch = camera_shake.max_angle * shake * noise(1, t);
+        let roll = camera_shake.max_angle * shake * noise(2, t);
+
+        let offset =
+            Vec3::new(noise(3, t), noise(4, t), noise(5, t)) * camera_shake.max_offset * shake;
+
+        let rotation = transform.rotation;
+        transform.translation += rotation * offset;
+        transform.rotation *= Quat::from_euler(EulerRot::YXZ, yawn, pitch, roll);
+    }
+}
+
+/// Smooth 1D value noise in the `[-1, 1]` range, `seed` selects an
+/// independent channel.
+fn noise(seed: u32, t: f32) -> f32 {
+    let hash = |n: i32| {
+        let mut x = (n as u32).wrapping_mul(0x9E37_79B9) ^ seed.wrapping_mul(0x85EB_CA6B);
+        x ^= x >> 15;
+        x = x.wrapping_mul(0x2C1B_3C6D);
+        x ^= x >> 12;
+
+        (x as f32 / u32::MAX as f32) * 2.0 - 1.0
+    };
+
+    let floor = t.floor();
+    let fraction = t - floor;
+    let smooth = fraction * fraction * (3.0 - 2.0 * fraction);
+
+    let a = hash(floor as i32);
+    let b = hash(floor as i32 + 1);
+
+    a + (b - a) * smooth
+}
@@ -0,0 +1,287 @@
+use bevy::{math::cubic_splines::CubicCurve, prelude::*};
+use bevy_rapier3d::prelude::*;
+use thiserror::Error;
+
+use crate::{
+    camera_controller::{CameraController, CameraTarget},
+    camera_mode::{CameraPose, CameraRig},
+    local_player::{LocalPlayer, PlayerAction, PlayerInput},
+    schedule::{CameraSet, InGameSet},
+    state::GameState,
+};
+
+const LETTERBOX_HEIGHT: f32 = 12.0;
+
+#[derive(Clone)]
+pub struct CameraKeyframe {
+    pub time: f32,
+    pub position: Vec3,
+    pub look_at: Vec3,
+    pub fov: f32,
+}
+
+#[derive(Debug, Error)]
+pub enum CutsceneError {
+    #[error("a cutscene needs at least two keyframes")]
+    TooFewKeyframes,
+    #[error("keyframe {0} does not come after the one before it")]
+    KeyframeOutOfOrder(usize),
+}
+
+/// A camera rail going through every keyframe, the camera position and the
+/// point it looks at are both interpolated with Catmull-Rom splines.
+#[derive(Clone)]
+pub struct Cutscene {
+    keyframes: Vec<CameraKeyframe>,
+    positions: CubicCurve<Vec3>,
+    look_ats: CubicCurve<Vec3>,
+}
+
+impl Cutscene {
+    /// Keyframe times have to strictly increase, so every segment of the
+    /// rail takes some time to play.
+    pub fn new(keyframes: Vec<CameraKeyframe>) -> Result<Cutscene, CutsceneError> {
+        if keyframes.len() < 2 {
+            return Err(CutsceneError::TooFewKeyframes);
+        }
+
+        if let Some(index) = keyframes
+            .windows(2)
+            .position(|keyframes| keyframes[1].time <= keyframes[0].time)
+        {
+            return Err(CutsceneError::KeyframeOutOfOrder(index + 1));
+        }
+
+        // Repeat the ends so the spline starts and stops on the first and
+        // last keyframes instead of only passing through the inner ones.
+        let spline = |point: fn(&CameraKeyframe) -> Vec3| {
+            let first = point(&keyframes[0]);
+            let last = point(&keyframes[keyframes.len() - 1]);
+
+            let points = std::iter::once(first)
+                .chain(keyframes.iter().map(point))
+                .chain(std::iter::once(last))
+                .collect::<Vec<_>>();
+
+            CubicCardinalSpline::new_catmull_rom(points).to_curve()
+        };
+
+        Ok(Cutscene {
+            positions: spline(|keyframe| keyframe.position),
+            look_ats: spline(|keyframe| keyframe.look_at),
+            keyframes,
+        })
+    }
+
+    fn duration(&self) -> f32 {
+        self.keyframes[self.keyframes.len() - 1].time
+    }
+
+    fn sample(&self, time: f32) -> CameraPose {
+        let last_segment = self.keyframes.len() - 2;
+        let segment = self
+            .keyframes
+            .windows(2)
+            .position(|keyframes| time < keyframes[1].time)
+            .unwrap_or(last_segment);
+
+        let from = &self.keyframes[segment];
+        let to = &self.keyframes[segment + 1];
+        let t = ((time - from.time) / (to.time - from.time)).clamp(0.0, 1.0);
+
+        let position = self.positions.position(segment as f32 + t);
+        let look_at = self.look_ats.position(segment as f32 + t);
+
+        CameraPose {
+            translation: position,
+            rotation: Transform::from_translation(position)
+                .looking_at(look_at, Vec3::Y)
+                .rotation,
+            fov: from.fov + (to.fov - from.fov) * t,
+        }
+    }
+}
+
+/// Sensor volume that starts its cutscene the first time the player walks in.
+#[derive(Component)]
+pub struct CutsceneTrigger(pub Cutscene);
+
+struct PlayingCutscene {
+    cutscene: Cutscene,
+    elapsed: f32,
+}
+
+#[derive(Resource, Default)]
+pub struct CutscenePlayback(Option<PlayingCutscene>);
+
+#[derive(Component)]
+struct Letterbox;
+
+pub struct CutscenePlugin;
+
+impl Plugin for CutscenePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CutscenePlayback>()
+            .add_systems(
+                Update,
+                trigger_cutscenes
+                    .run_if(not(cutscene_playing))
+                    .in_set(InGameSet::EntityUpdates),
+            )
+            .add_systems(
+                Update,
+                (skip_cutscene, play_cutscene)
+                    .chain()
+                    .run_if(in_state(GameState::InGame))
+                    .run_if(cutscene_playing)
+                    .before(CameraSet::Rig),
+            )
+            .add_systems(Update, (spawn_letterboxes, toggle_letterbox).chain());
+    }
+}
+
+/// Run condition for everything the player should not control while a
+/// cutscene is playing.
+pub fn cutscene_playing(playback: Res<CutscenePlayback>) -> bool {
+    playback.0.is_some()
+}
+
+/// Every player camera gets its own bars so they frame each viewport.
+fn spawn_letterboxes(
+    mut commands: Commands,
+    camera_query: Query<(Entity, &LocalPlayer), Added<CameraRig>>,
+) {
+    for (camera, local_player) in camera_query.iter() {
+        spawn_letterbox(&mut commands, camera, *local_player);
+    }
+}
+
+fn spawn_letterbox(commands: &mut Commands, camera: Entity, local_player: LocalPlayer) {
+    let bar = |style: Style| NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(LETTERBOX_HEIGHT),
+            left: Val::Px(0.0),
+            ..style
+        },
+        background_color: Color::BLACK.into(),
+        ..default()
+    };
+
+    let root = (
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        TargetCamera(camera),
+        Letterbox,
+        Name::new(format!("Player {} letterbox", local_player.0 + 1)),
+    );
+
+    commands.spawn(root).with_children(|parent| {
+        parent.spawn(bar(Style {
+            top: Val::Px(0.0),
+            ..default()
+        }));
+        parent.spawn(bar(Style {
+            bottom: Val::Px(0.0),
+            ..default()
+        }));
+    });
+}
+
+fn trigger_cutscenes(
+    mut commands: Commands,
+    mut playback: ResMut<CutscenePlayback>,
+    trigger_query: Query<(Entity, &CutsceneTrigger, &Collider, &GlobalTransform)>,
+    camera_query: Query<&CameraController>,
+    target_query: Query<&GlobalTransform, With<CameraTarget>>,
+) {
+    let target_positions = camera_query
+        .iter()
+        .filter_map(|camera_controller| camera_controller.target())
+        .filter_map(|target| target_query.get(target).ok())
+        .map(|transform| transform.translation())
+        .collect::<Vec<_>>();
+
+    for (entity, trigger, collider, transform) in trigger_query.iter() {
+        let (_, rotation, translation) = transform.to_scale_rotation_translation();
+
+        let triggered = target_positions
+            .iter()
+            .any(|position| collider.contains_point(translation, rotation, *position));
+
+        if !triggered {
+            continue;
+        }
+
+        playback.0 = Some(PlayingCutscene {
+            cutscene: trigger.0.clone(),
+            elapsed: 0.0,
+        });
+        commands.entity(entity).despawn_recursive();
+
+        return;
+    }
+}
+
+/// Any local player can skip the cutscene for everyone.
+fn skip_cutscene(player_query: Query<&PlayerInput>, mut playback: ResMut<CutscenePlayback>) {
+    let skipped = player_query.iter().any(|player_input| {
+        player_input
+            .actions
+            .just_pressed(PlayerAction::SkipCutscene)
+    });
+
+    if !skipped {
+        return;
+    }
+
+    if let Some(playing) = playback.0.as_mut() {
+        playing.elapsed = playing.cutscene.duration();
+    }
+}
+
+fn play_cutscene(
+    mut playback: ResMut<CutscenePlayback>,
+    mut camera_query: Query<&mut CameraRig>,
+    time: Res<Time>,
+) {
+    let Some(playing) = playback.0.as_mut() else {
+        return;
+    };
+
+    if playing.elapsed >= playing.cutscene.duration() {
+        for mut camera_rig in camera_query.iter_mut() {
+            camera_rig.stop_scripted();
+        }
+
+        playback.0 = None;
+        return;
+    }
+
+    let pose = playing.cutscene.sample(playing.elapsed);
+    for mut camera_rig in camera_query.iter_mut() {
+        camera_rig.play_scripted(pose);
+    }
+
+    playing.elapsed += time.delta_seconds();
+}
+
+fn toggle_letterbox(
+    playback: Res<CutscenePlayback>,
+    mut letterbox_query: Query<&mut Visibility, With<Letterbox>>,
+) {
+    for mut visibility in letterbox_query.iter_mut() {
+        *visibility = match playback.0.is_some() {
+            true => Visibility::Inherited,
+            false => Visibility::Hidden,
+        };
+    }
+}
@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    navigation::NavObstacle,
+    schedule::{InGameSet, SignalSet},
+    wiring::{SignalInput, Signals},
+};
+
+/// Layout of a door in a level file, it slides by `open_offset` at `speed`
+/// meters per second while `signal` is on.
+#[derive(Deserialize)]
+pub struct DoorDefinition {
+    pub name: String,
+    pub position: Vec3,
+    pub half_extents: Vec3,
+    pub open_offset: Vec3,
+    pub speed: f32,
+    pub signal: String,
+}
+
+#[derive(Component)]
+pub struct Door {
+    closed: Vec3,
+    open: Vec3,
+    speed: f32,
+}
+
+pub struct DoorPlugin;
+
+impl Plugin for DoorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            move_doors
+                .in_set(InGameSet::EntityUpdates)
+                .after(SignalSet::Evaluate),
+        );
+    }
+}
+
+/// Everything a door described in a level file needs.
+pub fn door_bundle(
+    definition: &DoorDefinition,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    assets: &AssetServer,
+) -> impl Bundle {
+    let half_extents = definition.half_extents;
+
+    (
+        PbrBundle {
+            mesh: meshes.add(Cuboid::from_size(half_extents * 2.0)),
+            material: materials.add(StandardMaterial {
+                base_color_texture: Some(assets.load("grid_textures/Pink/g2501.png")),
+                ..default()
+            }),
+            transform: Transform::from_translation(definition.position),
+            ..default()
+        },
+        Name::new(definition.name.clone()),
+        Door {
+            closed: definition.position,
+            open: definition.position + definition.open_offset,
+            speed: definition.speed,
+        },
+        SignalInput(definition.signal.clone()),
+        RigidBody::KinematicPositionBased,
+        Collider::cuboid(half_extents.x, half_extents.y, half_extents.z),
+        NavObstacle,
+    )
+}
+
+fn move_doors(
+    mut door_query: Query<(&Door, &SignalInput, &mut Transform)>,
+    signals: Res<Signals>,
+    time: Res<Time>,
+) {
+    for (door, input, mut transform) in door_query.iter_mut() {
+        let target = match signals.get(&input.0) {
+            true => door.open,
+            false => door.closed,
+        };
+
+        let step = door.speed * time.delta_seconds();
+        let remaining = target - transform.translation;
+
+        transform.translation += remaining.clamp_length_max(step);
+    }
+}
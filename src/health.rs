@@ -0,0 +1,85 @@
+use bevy::prelude::*;
+
+use crate::schedule::InGameSet;
+
+#[derive(Component, Reflect)]
+pub struct Health {
+    current: f32,
+    max: f32,
+    /// Damage is ignored while set, like during the i-frames of a dodge.
+    invulnerable: bool,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Health {
+        Health {
+            current: max,
+            max,
+            invulnerable: false,
+        }
+    }
+
+    pub fn fraction(&self) -> f32 {
+        self.current / self.max
+    }
+
+    pub fn restore(&mut self) {
+        self.current = self.max;
+    }
+
+    pub fn invulnerable(&self) -> bool {
+        self.invulnerable
+    }
+
+    pub fn set_invulnerable(&mut self, invulnerable: bool) {
+        self.invulnerable = invulnerable;
+    }
+}
+
+#[derive(Event)]
+pub struct Damage {
+    pub entity: Entity,
+    pub amount: f32,
+}
+
+/// Sent once when an entity's health drops to zero, whoever owns the entity
+/// decides what dying means for it.
+#[derive(Event)]
+pub struct Died {
+    pub entity: Entity,
+}
+
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Damage>()
+            .add_event::<Died>()
+            .add_systems(Update, apply_damage.in_set(InGameSet::EntityUpdates))
+            .register_type::<Health>();
+    }
+}
+
+fn apply_damage(
+    mut damage_events: EventReader<Damage>,
+    mut died_events: EventWriter<Died>,
+    mut health_query: Query<&mut Health>,
+) {
+    for damage in damage_events.read() {
+        let Ok(mut health) = health_query.get_mut(damage.entity) else {
+            continue;
+        };
+
+        if health.current <= 0.0 || health.invulnerable {
+            continue;
+        }
+
+        health.current = (health.current - damage.amount).max(0.0);
+
+        if health.current == 0.0 {
+            died_events.send(Died {
+                entity: damage.entity,
+            });
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use bevy::prelude::*;
+
+use crate::{
+    camera_controller::CameraController,
+    interaction::{Interactable, InteractionFocus},
+    local_player::{InputDevice, LocalPlayer, PlayerAction},
+    player::{PlayerState, Swimmer},
+};
+
+const CROSSHAIR_SIZE: f32 = 6.0;
+const BREATH_BAR_WIDTH: f32 = 160.0;
+const BREATH_BAR_HEIGHT: f32 = 8.0;
+
+#[derive(Component)]
+struct Crosshair(LocalPlayer);
+
+/// Shown while a player holds its breath under water.
+#[derive(Component)]
+struct BreathBar(LocalPlayer);
+
+#[derive(Component)]
+struct BreathFill(LocalPlayer);
+
+/// Tells a player what interacting would do, shown while something is in
+/// reach.
+#[derive(Component)]
+struct InteractionPrompt(LocalPlayer);
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (spawn_crosshairs, toggle_crosshairs).chain())
+            .add_systems(Update, (spawn_breath_bars, update_breath_bars).chain())
+            .add_systems(
+                Update,
+                (spawn_interaction_prompts, update_interaction_prompts).chain(),
+            );
+    }
+}
+
+/// Every player camera gets its own crosshair centered in its viewport.
+fn spawn_crosshairs(
+    mut commands: Commands,
+    camera_query: Query<(Entity, &LocalPlayer), Added<CameraController>>,
+) {
+    for (camera, local_player) in camera_query.iter() {
+        let root = (
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            TargetCamera(camera),
+            Crosshair(*local_player),
+            Name::new(format!("Player {} crosshair", local_player.0 + 1)),
+        );
+
+        let dot = NodeBundle {
+            style: Style {
+                width: Val::Px(CROSSHAIR_SIZE),
+                height: Val::Px(CROSSHAIR_SIZE),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            background_color: Color::WHITE.into(),
+            border_color: Color::BLACK.into(),
+            ..default()
+        };
+
+        commands.spawn(root).with_children(|parent| {
+            parent.spawn(dot);
+        });
+    }
+}
+
+fn toggle_crosshairs(
+    mut crosshair_query: Query<(&mut Visibility, &Crosshair)>,
+    player_query: Query<(&PlayerState, &LocalPlayer)>,
+) {
+    for (mut visibility, crosshair) in crosshair_query.iter_mut() {
+        let aiming = player_query.iter().any(|(player_state, local_player)| {
+            *local_player == crosshair.0 && *player_state == PlayerState::Aiming
+        });
+
+        *visibility = match aiming {
+            true => Visibility::Inherited,
+            false => Visibility::Hidden,
+        };
+    }
+}
+
+fn spawn_breath_bars(
+    mut commands: Commands,
+    camera_query: Query<(Entity, &LocalPlayer), Added<CameraController>>,
+) {
+    for (camera, local_player) in camera_query.iter() {
+        let root = (
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::FlexEnd,
+                    padding: UiRect::bottom(Val::Px(32.0)),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            TargetCamera(camera),
+            BreathBar(*local_player),
+            Name::new(format!("Player {} breath bar", local_player.0 + 1)),
+        );
+
+        let frame = NodeBundle {
+            style: Style {
+                width: Val::Px(BREATH_BAR_WIDTH),
+                height: Val::Px(BREATH_BAR_HEIGHT),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+            border_color: Color::BLACK.into(),
+            ..default()
+        };
+
+        let fill = (
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                background_color: Color::rgb(0.6, 0.85, 1.0).into(),
+                ..default()
+            },
+            BreathFill(*local_player),
+        );
+
+        commands.spawn(root).with_children(|parent| {
+            parent.spawn(frame).with_children(|parent| {
+                parent.spawn(fill);
+            });
+        });
+    }
+}
+
+fn update_breath_bars(
+    mut bar_query: Query<(&mut Visibility, &BreathBar)>,
+    mut fill_query: Query<(&mut Style, &BreathFill)>,
+    player_query: Query<(&Swimmer, &LocalPlayer)>,
+) {
+    let breath = |player: LocalPlayer| {
+        player_query
+            .iter()
+            .find(|(_, local_player)| **local_player == player)
+            .map(|(swimmer, _)| swimmer.breath_fraction())
+    };
+
+    for (mut visibility, breath_bar) in bar_query.iter_mut() {
+        let Some(fraction) = breath(breath_bar.0) else {
+            continue;
+        };
+
+        *visibility = match fraction < 1.0 {
+            true => Visibility::Inherited,
+            false => Visibility::Hidden,
+        };
+    }
+
+    for (mut style, breath_fill) in fill_query.iter_mut() {
+        if let Some(fraction) = breath(breath_fill.0) {
+            style.width = Val::Percent(fraction * 100.0);
+        }
+    }
+}
+
+fn spawn_interaction_prompts(
+    mut commands: Commands,
+    camera_query: Query<(Entity, &LocalPlayer), Added<CameraController>>,
+) {
+    for (camera, local_player) in camera_query.iter() {
+        let root = (
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::top(Val::Px(96.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            TargetCamera(camera),
+            Name::new(format!("Player {} interaction prompt", local_player.0 + 1)),
+        );
+
+        let mut text = TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.5));
+        text.visibility = Visibility::Hidden;
+
+        let prompt = (text, InteractionPrompt(*local_player));
+
+        commands.spawn(root).with_children(|parent| {
+            parent.spawn(prompt);
+        });
+    }
+}
+
+fn update_interaction_prompts(
+    mut prompt_query: Query<(&mut Text, &mut Visibility, &InteractionPrompt)>,
+    player_query: Query<(Ref<InteractionFocus>, Ref<InputDevice>, &LocalPlayer)>,
+    interactable_query: Query<&Interactable>,
+) {
+    for (focus, input_device, local_player) in player_query.iter() {
+        // Switching devices changes the button shown in the prompt.
+        if !focus.is_changed() && !input_device.is_changed() {
+            continue;
+        }
+
+        let Some((mut text, mut visibility, _)) = prompt_query
+            .iter_mut()
+            .find(|(_, _, prompt)| prompt.0 == *local_player)
+        else {
+            continue;
+        };
+
+        match focus
+            .0
+            .and_then(|entity| interactable_query.get(entity).ok())
+        {
+            Some(interactable) => {
+                text.sections[0].value = match input_device.binding_label(PlayerAction::Interact) {
+                    Some(button) => format!("[{button}] {}", interactable.prompt),
+                    None => interactable.prompt.clone(),
+                };
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
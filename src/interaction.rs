@@ -0,0 +1,117 @@
+use bevy::prelude::*;
+
+use crate::{
+    camera_controller::CameraController,
+    local_player::{LocalPlayer, PlayerAction, PlayerInput},
+    schedule::InGameSet,
+    state::GameState,
+};
+
+const INTERACTION_CONE: f32 = std::f32::consts::FRAC_PI_2;
+/// How many meters of distance looking straight away from a candidate is
+/// worth, so the camera facing breaks ties between nearby interactables.
+const FACING_WEIGHT: f32 = 1.0;
+
+/// Something a player can use from within `range`, `prompt` is shown on
+/// screen while it is the best candidate.
+#[derive(Component)]
+pub struct Interactable {
+    pub prompt: String,
+    pub range: f32,
+}
+
+impl Interactable {
+    pub fn new(prompt: impl Into<String>, range: f32) -> Interactable {
+        Interactable {
+            prompt: prompt.into(),
+            range,
+        }
+    }
+}
+
+/// The `Interactable` a player would use when pressing interact, if any.
+#[derive(Component, Default)]
+pub struct InteractionFocus(pub Option<Entity>);
+
+#[derive(Event)]
+pub struct Interacted {
+    pub entity: Entity,
+    pub interactor: Entity,
+}
+
+pub struct InteractionPlugin;
+
+impl Plugin for InteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Interacted>()
+            .add_systems(
+                Update,
+                (focus_interactables, interact)
+                    .chain()
+                    .in_set(InGameSet::UserInput),
+            )
+            .add_systems(OnExit(GameState::InGame), clear_focus);
+    }
+}
+
+/// Picks the closest interactable in range for every player, preferring the
+/// ones its camera looks at.
+fn focus_interactables(
+    mut player_query: Query<(&LocalPlayer, &Transform, &mut InteractionFocus)>,
+    camera_query: Query<(&LocalPlayer, &Transform), With<CameraController>>,
+    interactable_query: Query<(Entity, &Interactable, &GlobalTransform)>,
+) {
+    for (local_player, transform, mut focus) in player_query.iter_mut() {
+        let Some((_, camera_transform)) = camera_query
+            .iter()
+            .find(|(owner, _)| *owner == local_player)
+        else {
+            continue;
+        };
+
+        let camera_forward = *camera_transform.forward();
+
+        let best = interactable_query
+            .iter()
+            .filter_map(|(entity, interactable, interactable_transform)| {
+                let position = interactable_transform.translation();
+                let distance = position.distance(transform.translation);
+                let angle = camera_forward.angle_between(position - camera_transform.translation);
+
+                (distance <= interactable.range && angle <= INTERACTION_CONE)
+                    .then_some((entity, distance + angle * FACING_WEIGHT))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(entity, _)| entity);
+
+        // Avoid triggering change detection every frame.
+        if focus.0 != best {
+            focus.0 = best;
+        }
+    }
+}
+
+fn interact(
+    player_query: Query<(Entity, &PlayerInput, &InteractionFocus)>,
+    mut interacted_events: EventWriter<Interacted>,
+) {
+    for (player, player_input, focus) in player_query.iter() {
+        let Some(entity) = focus.0 else {
+            continue;
+        };
+
+        if player_input.actions.just_pressed(PlayerAction::Interact) {
+            interacted_events.send(Interacted {
+                entity,
+                interactor: player,
+            });
+        }
+    }
+}
+
+/// Hides the prompts while the game is paused.
+fn clear_focus(mut focus_query: Query<&mut InteractionFocus>) {
+    for mut focus in focus_query.iter_mut() {
+        focus.0 = None;
+    }
+}
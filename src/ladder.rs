@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use crate::interaction::Interactable;
+
+const LADDER_WIDTH: f32 = 0.5;
+const LADDER_THICKNESS: f32 = 0.06;
+const LADDER_INTERACTION_RANGE: f32 = 1.6;
+
+/// Layout of a ladder in a level file, `facing` points away from the wall
+/// towards where the climber stands.
+#[derive(Deserialize)]
+pub struct LadderDefinition {
+    pub name: String,
+    pub bottom: Vec3,
+    pub height: f32,
+    pub facing: Vec3,
+}
+
+#[derive(Component)]
+pub struct Ladder {
+    pub bottom: Vec3,
+    pub height: f32,
+    /// Horizontal direction from the ladder towards its climber.
+    pub facing: Vec3,
+}
+
+/// Everything a ladder described in a level file needs.
+pub fn ladder_bundle(
+    definition: &LadderDefinition,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    assets: &AssetServer,
+) -> impl Bundle {
+    let facing = Vec3::new(definition.facing.x, 0.0, definition.facing.z).normalize_or_zero();
+    let center = definition.bottom + Vec3::Y * definition.height * 0.5;
+
+    (
+        PbrBundle {
+            mesh: meshes.add(Cuboid::new(
+                LADDER_WIDTH,
+                definition.height,
+                LADDER_THICKNESS,
+            )),
+            material: materials.add(StandardMaterial {
+                base_color_texture: Some(assets.load("grid_textures/Red/g3215.png")),
+                ..default()
+            }),
+            transform: Transform::from_translation(center).looking_to(-facing, Vec3::Y),
+            ..default()
+        },
+        Name::new(definition.name.clone()),
+        Ladder {
+            bottom: definition.bottom,
+            height: definition.height,
+            facing,
+        },
+        Interactable::new("Climb", LADDER_INTERACTION_RANGE),
+        RigidBody::Fixed,
+        Collider::cuboid(
+            LADDER_WIDTH * 0.5,
+            definition.height * 0.5,
+            LADDER_THICKNESS * 0.5,
+        ),
+    )
+}
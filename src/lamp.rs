@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    schedule::{InGameSet, SignalSet},
+    wiring::{SignalInput, Signals},
+};
+
+/// Layout of a lamp in a level file, it shines while `signal` is on.
+#[derive(Deserialize)]
+pub struct LampDefinition {
+    pub name: String,
+    pub position: Vec3,
+    pub color: Color,
+    pub intensity: f32,
+    pub signal: String,
+}
+
+#[derive(Component)]
+pub struct Lamp {
+    intensity: f32,
+}
+
+pub struct LampPlugin;
+
+impl Plugin for LampPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            switch_lamps
+                .in_set(InGameSet::EntityUpdates)
+                .after(SignalSet::Evaluate),
+        );
+    }
+}
+
+/// Everything a lamp described in a level file needs, it starts out dark.
+pub fn lamp_bundle(definition: &LampDefinition) -> impl Bundle {
+    (
+        PointLightBundle {
+            point_light: PointLight {
+                color: definition.color,
+                intensity: 0.0,
+                ..default()
+            },
+            transform: Transform::from_translation(definition.position),
+            ..default()
+        },
+        Name::new(definition.name.clone()),
+        Lamp {
+            intensity: definition.intensity,
+        },
+        SignalInput(definition.signal.clone()),
+    )
+}
+
+fn switch_lamps(
+    mut lamp_query: Query<(&Lamp, &SignalInput, &mut PointLight)>,
+    signals: Res<Signals>,
+) {
+    for (lamp, input, mut light) in lamp_query.iter_mut() {
+        let intensity = match signals.get(&input.0) {
+            true => lamp.intensity,
+            false => 0.0,
+        };
+
+        // Only touch the light when it changes, so it isn't re-extracted every
+        // frame.
+        if light.intensity != intensity {
+            light.intensity = intensity;
+        }
+    }
+}
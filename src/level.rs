@@ -0,0 +1,158 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    door::{door_bundle, DoorDefinition},
+    ladder::{ladder_bundle, LadderDefinition},
+    lamp::{lamp_bundle, LampDefinition},
+    navigation::{NavMeshBounds, NavMeshSettings},
+    platform::{platform_bundle, PlatformDefinition},
+    switch::{spawn_switch, SwitchDefinition},
+    water::{water_bundle, WaterDefinition},
+    wiring::{LogicDefinition, LogicGraph, SignalInput},
+};
+
+/// Interactive parts of a level loaded from a `.level.ron` file, the static
+/// geometry is still spawned by the `WorldPlugin`. Switches, doors, lamps and
+/// platforms are wired together through named signals, combined by the
+/// `logic` nodes.
+#[derive(Asset, TypePath, Deserialize)]
+pub struct Level {
+    #[serde(default)]
+    pub platforms: Vec<PlatformDefinition>,
+    #[serde(default)]
+    pub water: Vec<WaterDefinition>,
+    #[serde(default)]
+    pub ladders: Vec<LadderDefinition>,
+    #[serde(default)]
+    pub switches: Vec<SwitchDefinition>,
+    #[serde(default)]
+    pub doors: Vec<DoorDefinition>,
+    #[serde(default)]
+    pub lamps: Vec<LampDefinition>,
+    #[serde(default)]
+    pub logic: Vec<LogicDefinition>,
+    #[serde(default)]
+    pub nav_mesh_bounds: Option<NavMeshBounds>,
+}
+
+#[derive(Resource)]
+struct CurrentLevel(Handle<Level>);
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Level>()
+            .init_asset_loader::<LevelLoader>()
+            .add_systems(Startup, load_level)
+            .add_systems(Update, spawn_level);
+    }
+}
+
+fn load_level(mut commands: Commands, assets: Res<AssetServer>) {
+    commands.insert_resource(CurrentLevel(assets.load("levels/prototype.level.ron")));
+}
+
+fn spawn_level(
+    mut commands: Commands,
+    mut level_events: EventReader<AssetEvent<Level>>,
+    current_level: Res<CurrentLevel>,
+    levels: Res<Assets<Level>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    assets: Res<AssetServer>,
+) {
+    for event in level_events.read() {
+        if !event.is_loaded_with_dependencies(&current_level.0) {
+            continue;
+        }
+
+        let Some(level) = levels.get(&current_level.0) else {
+            continue;
+        };
+
+        for platform in &level.platforms {
+            let mut entity = commands.spawn(platform_bundle(
+                platform,
+                &mut meshes,
+                &mut materials,
+                &assets,
+            ));
+
+            if let Some(signal) = &platform.signal {
+                entity.insert(SignalInput(signal.clone()));
+            }
+        }
+
+        for water in &level.water {
+            commands.spawn(water_bundle(water, &mut meshes, &mut materials));
+        }
+
+        for ladder in &level.ladders {
+            commands.spawn(ladder_bundle(ladder, &mut meshes, &mut materials, &assets));
+        }
+
+        for switch in &level.switches {
+            spawn_switch(&mut commands, switch, &mut meshes, &mut materials, &assets);
+        }
+
+        for door in &level.doors {
+            commands.spawn(door_bundle(door, &mut meshes, &mut materials, &assets));
+        }
+
+        for lamp in &level.lamps {
+            commands.spawn(lamp_bundle(lamp));
+        }
+
+        commands.insert_resource(LogicGraph::new(&level.logic));
+
+        if let Some(bounds) = level.nav_mesh_bounds {
+            commands.insert_resource(NavMeshSettings {
+                min: bounds.min,
+                max: bounds.max,
+                ..default()
+            });
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+#[derive(Debug, Error)]
+pub enum LevelLoaderError {
+    #[error("could not read the level: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse the level: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = LevelLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Level, LevelLoaderError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            Ok(ron::de::from_bytes::<Level>(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
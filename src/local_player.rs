@@ -0,0 +1,287 @@
+use bevy::{
+    input::{mouse::*, InputSystem},
+    prelude::*,
+    render::camera::Viewport,
+    window::PrimaryWindow,
+};
+
+const MAX_LOCAL_PLAYERS: usize = 4;
+const GAMEPAD_LOOK_SPEED: f32 = 0.4;
+const GAMEPAD_ZOOM_SPEED: f32 = 10.0;
+
+/// Identifies which local player an entity belongs to, it is shared by the
+/// player character, its camera and its camera target.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug, Reflect)]
+pub struct LocalPlayer(pub usize);
+
+/// How many players share the screen, set with `--players <count>`.
+#[derive(Resource)]
+pub struct LocalPlayers {
+    pub count: usize,
+}
+
+impl LocalPlayers {
+    fn from_args() -> LocalPlayers {
+        let args = std::env::args().collect::<Vec<_>>();
+
+        let count = args
+            .windows(2)
+            .find(|args| args[0] == "--players")
+            .and_then(|args| args[1].parse::<usize>().ok())
+            .unwrap_or(1)
+            .clamp(1, MAX_LOCAL_PLAYERS);
+
+        LocalPlayers { count }
+    }
+
+    /// The first player plays with keyboard and mouse, everyone else gets a
+    /// gamepad in connection order.
+    pub fn input_device(&self, player: LocalPlayer) -> InputDevice {
+        match player.0 {
+            0 => InputDevice::KeyboardMouse,
+            index => InputDevice::Gamepad(Gamepad::new(index - 1)),
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+pub enum InputDevice {
+    KeyboardMouse,
+    Gamepad(Gamepad),
+}
+
+impl InputDevice {
+    /// Name of the button `action` is bound to on this device, to show in
+    /// prompts.
+    pub fn binding_label(&self, action: PlayerAction) -> Option<String> {
+        match self {
+            InputDevice::KeyboardMouse => KEYBOARD_BINDINGS
+                .iter()
+                .find(|(bound, _)| *bound == action)
+                .map(|(_, key)| format!("{key:?}").trim_start_matches("Key").to_owned())
+                .or_else(|| {
+                    MOUSE_BINDINGS
+                        .iter()
+                        .find(|(bound, _)| *bound == action)
+                        .map(|(_, button)| format!("Mouse {button:?}"))
+                }),
+            InputDevice::Gamepad(_) => GAMEPAD_BINDINGS
+                .iter()
+                .find(|(bound, _)| *bound == action)
+                .map(|(_, button_type)| format!("{button_type:?}")),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PlayerAction {
+    Jump,
+    Run,
+    Aim,
+    Fire,
+    Reload,
+    LockOn,
+    CycleTarget,
+    SwapShoulder,
+    Crouch,
+    Dodge,
+    Interact,
+    SkipCutscene,
+}
+
+/// Device independent input of a local player, refreshed every frame from
+/// its `InputDevice`.
+#[derive(Component, Default)]
+pub struct PlayerInput {
+    /// Movement relative to the camera, `y` points forward and `x` right.
+    pub movement: Vec2,
+    /// Camera rotation as a fraction of a full turn.
+    pub look: Vec2,
+    pub zoom: f32,
+    pub actions: ButtonInput<PlayerAction>,
+}
+
+pub struct LocalPlayerPlugin;
+
+impl Plugin for LocalPlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LocalPlayers::from_args())
+            .add_systems(PreUpdate, read_player_input.after(InputSystem))
+            .add_systems(Update, update_viewports)
+            .register_type::<LocalPlayer>();
+    }
+}
+
+const KEYBOARD_BINDINGS: [(PlayerAction, KeyCode); 9] = [
+    (PlayerAction::Jump, KeyCode::Space),
+    (PlayerAction::Run, KeyCode::ShiftLeft),
+    (PlayerAction::Reload, KeyCode::KeyR),
+    (PlayerAction::CycleTarget, KeyCode::Tab),
+    (PlayerAction::SwapShoulder, KeyCode::KeyV),
+    (PlayerAction::Crouch, KeyCode::KeyC),
+    (PlayerAction::Dodge, KeyCode::AltLeft),
+    (PlayerAction::Interact, KeyCode::KeyE),
+    (PlayerAction::SkipCutscene, KeyCode::Enter),
+];
+
+const MOUSE_BINDINGS: [(PlayerAction, MouseButton); 3] = [
+    (PlayerAction::Aim, MouseButton::Right),
+    (PlayerAction::Fire, MouseButton::Left),
+    (PlayerAction::LockOn, MouseButton::Middle),
+];
+
+const GAMEPAD_BINDINGS: [(PlayerAction, GamepadButtonType); 12] = [
+    (PlayerAction::Jump, GamepadButtonType::South),
+    (PlayerAction::Run, GamepadButtonType::LeftThumb),
+    (PlayerAction::Aim, GamepadButtonType::LeftTrigger2),
+    (PlayerAction::Fire, GamepadButtonType::RightTrigger2),
+    (PlayerAction::Reload, GamepadButtonType::West),
+    (PlayerAction::LockOn, GamepadButtonType::RightThumb),
+    (PlayerAction::CycleTarget, GamepadButtonType::DPadRight),
+    (PlayerAction::SwapShoulder, GamepadButtonType::North),
+    (PlayerAction::Crouch, GamepadButtonType::East),
+    (PlayerAction::Dodge, GamepadButtonType::RightTrigger),
+    (PlayerAction::Interact, GamepadButtonType::DPadLeft),
+    (PlayerAction::SkipCutscene, GamepadButtonType::Start),
+];
+
+#[allow(clippy::too_many_arguments)]
+fn read_player_input(
+    mut player_query: Query<(&InputDevice, &mut PlayerInput)>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut mouse_motion_events: EventReader<MouseMotion>,
+    mut scroll_events: EventReader<MouseWheel>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    time: Res<Time>,
+) {
+    let mouse_delta = mouse_motion_events
+        .read()
+        .map(|event| event.delta)
+        .sum::<Vec2>();
+    let scroll_delta = scroll_events.read().map(|event| -event.y).sum::<f32>();
+    let window_width = window_query
+        .get_single()
+        .map_or(1.0, |window| window.width());
+
+    for (input_device, mut player_input) in player_query.iter_mut() {
+        player_input.actions.clear();
+
+        match *input_device {
+            InputDevice::KeyboardMouse => {
+                let mut movement = Vec2::ZERO;
+
+                if keys.pressed(KeyCode::KeyW) {
+                    movement += Vec2::Y;
+                }
+
+                if keys.pressed(KeyCode::KeyS) {
+                    movement -= Vec2::Y;
+                }
+
+                if keys.pressed(KeyCode::KeyD) {
+                    movement += Vec2::X;
+                }
+
+                if keys.pressed(KeyCode::KeyA) {
+                    movement -= Vec2::X;
+                }
+
+                player_input.movement = movement;
+                player_input.look = mouse_delta / window_width;
+                player_input.zoom = scroll_delta;
+
+                for (action, key) in KEYBOARD_BINDINGS {
+                    set_action(&mut player_input.actions, action, keys.pressed(key));
+                }
+
+                for (action, button) in MOUSE_BINDINGS {
+                    set_action(&mut player_input.actions, action, mouse.pressed(button));
+                }
+            }
+            InputDevice::Gamepad(gamepad) => {
+                let axis = |axis_type| {
+                    gamepad_axes
+                        .get(GamepadAxis::new(gamepad, axis_type))
+                        .unwrap_or(0.0)
+                };
+                let button =
+                    |button_type| gamepad_buttons.pressed(GamepadButton::new(gamepad, button_type));
+
+                player_input.movement = Vec2::new(
+                    axis(GamepadAxisType::LeftStickX),
+                    axis(GamepadAxisType::LeftStickY),
+                );
+                player_input.look = Vec2::new(
+                    axis(GamepadAxisType::RightStickX),
+                    -axis(GamepadAxisType::RightStickY),
+                ) * GAMEPAD_LOOK_SPEED
+                    * time.delta_seconds();
+
+                let mut zoom = 0.0;
+                if button(GamepadButtonType::DPadDown) {
+                    zoom += 1.0;
+                }
+                if button(GamepadButtonType::DPadUp) {
+                    zoom -= 1.0;
+                }
+                player_input.zoom = zoom * GAMEPAD_ZOOM_SPEED * time.delta_seconds();
+
+                for (action, button_type) in GAMEPAD_BINDINGS {
+                    set_action(&mut player_input.actions, action, button(button_type));
+                }
+            }
+        }
+    }
+}
+
+fn set_action(actions: &mut ButtonInput<PlayerAction>, action: PlayerAction, pressed: bool) {
+    match pressed {
+        true => actions.press(action),
+        false => actions.release(action),
+    }
+}
+
+/// Splits the window between every local player camera: side by side for
+/// two players and in quarters for three or four.
+fn update_viewports(
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut camera_query: Query<(&LocalPlayer, &mut Camera)>,
+    local_players: Res<LocalPlayers>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+
+    let window_size = UVec2::new(window.physical_width(), window.physical_height());
+    let (columns, rows) = match local_players.count {
+        1 => (1, 1),
+        2 => (2, 1),
+        _ => (2, 2),
+    };
+    let size = UVec2::new(window_size.x / columns, window_size.y / rows);
+
+    if size.x == 0 || size.y == 0 {
+        return;
+    }
+
+    for (local_player, mut camera) in camera_query.iter_mut() {
+        let index = local_player.0 as u32;
+        let viewport = Viewport {
+            physical_position: UVec2::new(index % columns, index / columns) * size,
+            physical_size: size,
+            ..default()
+        };
+
+        let changed = camera.viewport.as_ref().is_none_or(|current| {
+            current.physical_position != viewport.physical_position
+                || current.physical_size != viewport.physical_size
+        });
+
+        if changed {
+            camera.viewport = Some(viewport);
+        }
+    }
+}
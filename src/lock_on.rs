@@ -0,0 +1,224 @@
+use bevy::prelude::*;
+
+use crate::{
+    camera_controller::{CameraController, CameraTarget},
+    local_player::{LocalPlayer, PlayerAction, PlayerInput},
+    schedule::InGameSet,
+};
+
+const LOCK_ON_RANGE: f32 = 15.0;
+const LOCK_ON_BREAK_RANGE: f32 = 20.0;
+const LOCK_ON_CONE: f32 = std::f32::consts::FRAC_PI_4;
+
+#[derive(Component)]
+pub struct Targetable;
+
+/// The `Targetable` a player is locked on to, if any.
+#[derive(Component, Default)]
+pub struct LockOnTarget(pub Option<Entity>);
+
+pub struct LockOnPlugin;
+
+impl Plugin for LockOnPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (toggle_lock_on, cycle_lock_on_target, break_lock_on)
+                .chain()
+                .in_set(InGameSet::UserInput),
+        )
+        .add_systems(
+            Update,
+            (frame_lock_on_target, draw_lock_on_marker).in_set(InGameSet::EntityUpdates),
+        );
+    }
+}
+
+fn toggle_lock_on(
+    mut player_query: Query<(&LocalPlayer, &PlayerInput, &mut LockOnTarget)>,
+    camera_query: Query<(&LocalPlayer, &Transform, &CameraController)>,
+    targetable_query: Query<(Entity, &GlobalTransform), With<Targetable>>,
+    target_query: Query<&GlobalTransform, With<CameraTarget>>,
+) {
+    for (local_player, player_input, mut lock_on_target) in player_query.iter_mut() {
+        if !player_input.actions.just_pressed(PlayerAction::LockOn) {
+            continue;
+        }
+
+        if lock_on_target.0.is_some() {
+            lock_on_target.0 = None;
+            continue;
+        }
+
+        let Some((_, camera_transform, camera_controller)) = camera_query
+            .iter()
+            .find(|(owner, _, _)| *owner == local_player)
+        else {
+            continue;
+        };
+
+        let Some(player_position) = followed_position(camera_controller, &target_query) else {
+            continue;
+        };
+
+        let camera_forward = *camera_transform.forward();
+
+        lock_on_target.0 = targetable_query
+            .iter()
+            .filter(|(_, transform)| {
+                let to_target = transform.translation() - camera_transform.translation;
+
+                transform.translation().distance(player_position) <= LOCK_ON_RANGE
+                    && camera_forward.angle_between(to_target) <= LOCK_ON_CONE
+            })
+            .min_by(|(_, a), (_, b)| {
+                let distance_a = a.translation().distance_squared(player_position);
+                let distance_b = b.translation().distance_squared(player_position);
+
+                distance_a.total_cmp(&distance_b)
+            })
+            .map(|(entity, _)| entity);
+    }
+}
+
+fn cycle_lock_on_target(
+    mut player_query: Query<(&LocalPlayer, &PlayerInput, &mut LockOnTarget)>,
+    camera_query: Query<(&LocalPlayer, &Transform, &CameraController)>,
+    targetable_query: Query<(Entity, &GlobalTransform), With<Targetable>>,
+    target_query: Query<&GlobalTransform, With<CameraTarget>>,
+) {
+    for (local_player, player_input, mut lock_on_target) in player_query.iter_mut() {
+        let Some(current_target) = lock_on_target.0 else {
+            continue;
+        };
+
+        if !player_input.actions.just_pressed(PlayerAction::CycleTarget) {
+            continue;
+        }
+
+        let Some((_, camera_transform, camera_controller)) = camera_query
+            .iter()
+            .find(|(owner, _, _)| *owner == local_player)
+        else {
+            continue;
+        };
+
+        let Some(player_position) = followed_position(camera_controller, &target_query) else {
+            continue;
+        };
+
+        // Order the candidates from left to right as seen from the camera, so
+        // cycling sweeps across the screen.
+        let screen_angle = |position: Vec3| {
+            let local =
+                camera_transform.rotation.inverse() * (position - camera_transform.translation);
+            local.x.atan2(-local.z)
+        };
+
+        let mut candidates = targetable_query
+            .iter()
+            .filter(|(_, transform)| {
+                transform.translation().distance(player_position) <= LOCK_ON_RANGE
+            })
+            .map(|(entity, transform)| (entity, screen_angle(transform.translation())))
+            .collect::<Vec<_>>();
+
+        candidates.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        let next = candidates
+            .iter()
+            .position(|(entity, _)| *entity == current_target)
+            .map_or(0, |index| (index + 1) % candidates.len());
+
+        if let Some((entity, _)) = candidates.get(next) {
+            lock_on_target.0 = Some(*entity);
+        }
+    }
+}
+
+fn break_lock_on(
+    mut player_query: Query<(&LocalPlayer, &mut LockOnTarget)>,
+    camera_query: Query<(&LocalPlayer, &CameraController)>,
+    targetable_query: Query<&GlobalTransform, With<Targetable>>,
+    target_query: Query<&GlobalTransform, With<CameraTarget>>,
+) {
+    for (local_player, mut lock_on_target) in player_query.iter_mut() {
+        let Some(target) = lock_on_target.0 else {
+            continue;
+        };
+
+        let player_position = camera_query
+            .iter()
+            .find(|(owner, _)| *owner == local_player)
+            .and_then(|(_, camera_controller)| followed_position(camera_controller, &target_query));
+
+        let Some(player_position) = player_position else {
+            lock_on_target.0 = None;
+            continue;
+        };
+
+        let in_range = targetable_query.get(target).is_ok_and(|transform| {
+            transform.translation().distance(player_position) <= LOCK_ON_BREAK_RANGE
+        });
+
+        if !in_range {
+            lock_on_target.0 = None;
+        }
+    }
+}
+
+/// Lock-on distances are measured from whatever the camera follows.
+fn followed_position(
+    camera_controller: &CameraController,
+    target_query: &Query<&GlobalTransform, With<CameraTarget>>,
+) -> Option<Vec3> {
+    camera_controller
+        .target()
+        .and_then(|target| target_query.get(target).ok())
+        .map(|transform| transform.translation())
+}
+
+fn frame_lock_on_target(
+    player_query: Query<(&LocalPlayer, &LockOnTarget)>,
+    mut camera_query: Query<(&LocalPlayer, &mut CameraController)>,
+    targetable_query: Query<&GlobalTransform, With<Targetable>>,
+) {
+    for (local_player, mut camera_controller) in camera_query.iter_mut() {
+        let framing_target = player_query
+            .iter()
+            .find(|(owner, _)| *owner == local_player)
+            .and_then(|(_, lock_on_target)| lock_on_target.0)
+            .and_then(|target| targetable_query.get(target).ok())
+            .map(|transform| transform.translation());
+
+        camera_controller.set_framing_target(framing_target);
+    }
+}
+
+fn draw_lock_on_marker(
+    mut gizmos: Gizmos,
+    player_query: Query<(&LocalPlayer, &LockOnTarget)>,
+    targetable_query: Query<&GlobalTransform, With<Targetable>>,
+    camera_query: Query<(&LocalPlayer, &Transform), With<CameraController>>,
+) {
+    for (local_player, lock_on_target) in player_query.iter() {
+        let Some(transform) = lock_on_target
+            .0
+            .and_then(|target| targetable_query.get(target).ok())
+        else {
+            continue;
+        };
+
+        let Some((_, camera_transform)) = camera_query
+            .iter()
+            .find(|(owner, _)| *owner == local_player)
+        else {
+            continue;
+        };
+
+        let normal = Direction3d::new(camera_transform.translation - transform.translation())
+            .unwrap_or(Direction3d::Y);
+
+        gizmos.circle(transform.translation(), normal, 0.25, Color::RED);
+    }
+}
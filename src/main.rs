@@ -1,19 +1,59 @@
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 
+mod ai;
+mod aim;
 mod camera;
 mod camera_controller;
+mod camera_mode;
+mod camera_shake;
+mod cutscene;
+mod door;
+mod health;
+mod hud;
+mod interaction;
+mod ladder;
+mod lamp;
+mod level;
+mod local_player;
+mod lock_on;
+mod navigation;
+mod npc;
+mod perception;
+mod platform;
 mod player;
 mod schedule;
 mod state;
+mod switch;
+mod water;
+mod wiring;
 mod world;
 
+use ai::AiPlugin;
+use aim::AimPlugin;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use camera::CameraPlugin;
 use camera_controller::CameraControllerPlugin;
+use camera_mode::CameraModePlugin;
+use camera_shake::CameraShakePlugin;
+use cutscene::CutscenePlugin;
+use door::DoorPlugin;
+use health::HealthPlugin;
+use hud::HudPlugin;
+use interaction::InteractionPlugin;
+use lamp::LampPlugin;
+use level::LevelPlugin;
+use local_player::LocalPlayerPlugin;
+use lock_on::LockOnPlugin;
+use navigation::NavigationPlugin;
+use npc::NpcPlugin;
+use perception::PerceptionPlugin;
+use platform::PlatformPlugin;
 use player::PlayerPlugin;
 use schedule::SchedulePlugin;
 use state::StatePlugin;
+use switch::SwitchPlugin;
+use wiring::WiringPlugin;
 use world::WorldPlugin;
 
 fn main() {
@@ -22,12 +62,32 @@ fn main() {
         .add_plugins(WorldInspectorPlugin::new()) // egui integration
         .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
         .add_plugins(RapierDebugRenderPlugin::default())
-        .add_plugins((StatePlugin, SchedulePlugin))
+        .add_plugins((StatePlugin, SchedulePlugin, LocalPlayerPlugin))
         .add_plugins((
             PlayerPlugin,
             CameraPlugin,
             CameraControllerPlugin,
+            CameraModePlugin,
+            CameraShakePlugin,
             WorldPlugin,
+            AimPlugin,
+            HudPlugin,
+            LockOnPlugin,
+            CutscenePlugin,
+            NpcPlugin,
+            NavigationPlugin,
+            HealthPlugin,
+            AiPlugin,
+            PerceptionPlugin,
+        ))
+        .add_plugins((
+            LevelPlugin,
+            PlatformPlugin,
+            InteractionPlugin,
+            WiringPlugin,
+            SwitchPlugin,
+            DoorPlugin,
+            LampPlugin,
         ))
         .run();
 }
@@ -0,0 +1,556 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use crate::{player::CharacterInput, schedule::CharacterSet};
+
+const WAYPOINT_REACHED_DISTANCE: f32 = 0.15;
+const NEAREST_CELL_SEARCH_RADIUS: i32 = 3;
+
+/// Area covered by the navmesh and the size of the characters walking on it.
+/// The area defaults to the floor of the prototype world, a level file can
+/// set it with `NavMeshBounds`.
+#[derive(Resource)]
+pub struct NavMeshSettings {
+    pub min: Vec3,
+    pub max: Vec3,
+    pub cell_size: f32,
+    pub agent_radius: f32,
+    pub agent_height: f32,
+    pub step_height: f32,
+}
+
+impl Default for NavMeshSettings {
+    fn default() -> Self {
+        NavMeshSettings {
+            min: Vec3::new(-4.0, -1.0, -4.0),
+            max: Vec3::new(4.0, 4.0, 4.0),
+            cell_size: 0.25,
+            agent_radius: 0.3,
+            agent_height: 1.8,
+            step_height: 0.25,
+        }
+    }
+}
+
+/// Area of a level covered by the navmesh, in a level file.
+#[derive(Deserialize, Clone, Copy)]
+pub struct NavMeshBounds {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+/// Marks a kinematic body that blocks the navmesh wherever it currently is,
+/// like a door. The navmesh is rebuilt whenever it moves.
+#[derive(Component)]
+pub struct NavObstacle;
+
+/// Walkable ground sampled on a regular grid, every cell stores the height of
+/// the top-most surface a character fits on, or `None` when blocked.
+#[derive(Resource, Default)]
+pub struct NavMesh {
+    origin: Vec3,
+    cell_size: f32,
+    step_height: f32,
+    width: i32,
+    depth: i32,
+    cells: Vec<Option<f32>>,
+}
+
+type Cell = (i32, i32);
+
+impl NavMesh {
+    /// Samples the static colliders matched by `filter`. It only needs the
+    /// physics context so it can be built without running the app.
+    pub fn build(
+        rapier_context: &RapierContext,
+        settings: &NavMeshSettings,
+        filter: QueryFilter,
+    ) -> NavMesh {
+        let width = ((settings.max.x - settings.min.x) / settings.cell_size).ceil() as i32;
+        let depth = ((settings.max.z - settings.min.z) / settings.cell_size).ceil() as i32;
+
+        // The clearance check starts one step above the ground so the ground
+        // itself and small ledges the character can step on don't block it.
+        let half_height =
+            ((settings.agent_height - settings.step_height) * 0.5 - settings.agent_radius).max(0.0);
+        let capsule = Collider::capsule_y(half_height, settings.agent_radius);
+        let capsule_center = settings.step_height + settings.agent_radius + half_height;
+
+        let mut nav_mesh = NavMesh {
+            origin: settings.min,
+            cell_size: settings.cell_size,
+            step_height: settings.step_height,
+            width,
+            depth,
+            cells: Vec::with_capacity((width * depth) as usize),
+        };
+
+        for z in 0..depth {
+            for x in 0..width {
+                let center = nav_mesh.cell_center((x, z));
+                let origin = Vec3::new(center.x, settings.max.y, center.z);
+
+                let ground = rapier_context
+                    .cast_ray(
+                        origin,
+                        Vec3::NEG_Y,
+                        settings.max.y - settings.min.y,
+                        true,
+                        filter,
+                    )
+                    .map(|(_, toi)| settings.max.y - toi);
+
+                let walkable = ground.filter(|ground| {
+                    let mut blocked = false;
+                    rapier_context.intersections_with_shape(
+                        Vec3::new(center.x, ground + capsule_center, center.z),
+                        Quat::IDENTITY,
+                        &capsule,
+                        filter,
+                        |_| {
+                            blocked = true;
+                            false
+                        },
+                    );
+
+                    !blocked
+                });
+
+                nav_mesh.cells.push(walkable);
+            }
+        }
+
+        nav_mesh
+    }
+
+    /// Shortest path between two points as a list of waypoints ending at
+    /// `to`, or `None` when no walkable route connects them.
+    pub fn find_path(&self, from: Vec3, to: Vec3) -> Option<Vec<Vec3>> {
+        let start = self.nearest_walkable(from)?;
+        let goal = self.nearest_walkable(to)?;
+
+        let cells = self.a_star(start, goal)?;
+        let mut path = self.smooth(from, &cells);
+
+        if let Some(last) = path.last_mut() {
+            *last = Vec3::new(to.x, self.height(goal)?, to.z);
+        }
+
+        Some(path)
+    }
+
+    fn a_star(&self, start: Cell, goal: Cell) -> Option<Vec<Cell>> {
+        let heuristic = |(x, z): Cell| {
+            let dx = (x - goal.0).abs() as f32;
+            let dz = (z - goal.1).abs() as f32;
+            dx.max(dz) + (std::f32::consts::SQRT_2 - 1.0) * dx.min(dz)
+        };
+
+        let mut costs = vec![f32::INFINITY; self.cells.len()];
+        let mut came_from = vec![None; self.cells.len()];
+        let mut open = BinaryHeap::new();
+
+        costs[self.index(start)] = 0.0;
+        open.push(OpenCell {
+            estimate: heuristic(start),
+            cell: start,
+        });
+
+        while let Some(OpenCell { cell, .. }) = open.pop() {
+            if cell == goal {
+                let mut path = vec![cell];
+                let mut current = cell;
+                while let Some(previous) = came_from[self.index(current)] {
+                    path.push(previous);
+                    current = previous;
+                }
+                path.reverse();
+
+                return Some(path);
+            }
+
+            let cost = costs[self.index(cell)];
+
+            for (neighbour, step_cost) in self.neighbours(cell) {
+                let neighbour_cost = cost + step_cost;
+                let index = self.index(neighbour);
+
+                if neighbour_cost < costs[index] {
+                    costs[index] = neighbour_cost;
+                    came_from[index] = Some(cell);
+                    open.push(OpenCell {
+                        estimate: neighbour_cost + heuristic(neighbour),
+                        cell: neighbour,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    fn neighbours(&self, (x, z): Cell) -> impl Iterator<Item = (Cell, f32)> + '_ {
+        const OFFSETS: [(i32, i32); 8] = [
+            (1, 0),
+            (-1, 0),
+            (0, 1),
+            (0, -1),
+            (1, 1),
+            (1, -1),
+            (-1, 1),
+            (-1, -1),
+        ];
+
+        OFFSETS.iter().filter_map(move |&(dx, dz)| {
+            let neighbour = (x + dx, z + dz);
+
+            if !self.connected((x, z), neighbour) {
+                return None;
+            }
+
+            // Diagonal moves must not cut the corner of a blocked cell.
+            if dx != 0
+                && dz != 0
+                && (!self.connected((x, z), (x + dx, z)) || !self.connected((x, z), (x, z + dz)))
+            {
+                return None;
+            }
+
+            let cost = match dx != 0 && dz != 0 {
+                true => std::f32::consts::SQRT_2,
+                false => 1.0,
+            };
+
+            Some((neighbour, cost))
+        })
+    }
+
+    fn connected(&self, from: Cell, to: Cell) -> bool {
+        match (self.height(from), self.height(to)) {
+            (Some(from), Some(to)) => (from - to).abs() <= self.step_height,
+            _ => false,
+        }
+    }
+
+    /// Drops every waypoint that can be skipped by walking straight to a
+    /// later one.
+    fn smooth(&self, from: Vec3, cells: &[Cell]) -> Vec<Vec3> {
+        let mut path = Vec::new();
+        let mut anchor = from;
+        let mut index = 0;
+
+        while index < cells.len() {
+            let furthest = (index..cells.len())
+                .rev()
+                .find(|candidate| self.walkable_line(anchor, self.cell_center(cells[*candidate])))
+                .unwrap_or(index);
+
+            let cell = cells[furthest];
+            let waypoint = self.cell_center(cell) + Vec3::Y * self.height(cell).unwrap_or(from.y);
+            path.push(waypoint);
+            anchor = waypoint;
+            index = furthest + 1;
+        }
+
+        path
+    }
+
+    fn walkable_line(&self, from: Vec3, to: Vec3) -> bool {
+        let steps = (from.xz().distance(to.xz()) / (self.cell_size * 0.5)).ceil() as i32;
+        let mut previous = self.cell_at(from);
+
+        for step in 1..=steps.max(1) {
+            let cell = self.cell_at(from.lerp(to, step as f32 / steps.max(1) as f32));
+
+            if cell != previous && !self.connected(previous, cell) {
+                return false;
+            }
+
+            previous = cell;
+        }
+
+        true
+    }
+
+    fn nearest_walkable(&self, position: Vec3) -> Option<Cell> {
+        let (x, z) = self.cell_at(position);
+
+        (-NEAREST_CELL_SEARCH_RADIUS..=NEAREST_CELL_SEARCH_RADIUS)
+            .flat_map(|dz| {
+                (-NEAREST_CELL_SEARCH_RADIUS..=NEAREST_CELL_SEARCH_RADIUS)
+                    .map(move |dx| (x + dx, z + dz))
+            })
+            .filter_map(|cell| self.height(cell).map(|height| (cell, height)))
+            .min_by(|(a, height_a), (b, height_b)| {
+                let distance = |cell: Cell, height: f32| {
+                    (self.cell_center(cell) + Vec3::Y * height).distance_squared(position)
+                };
+
+                distance(*a, *height_a).total_cmp(&distance(*b, *height_b))
+            })
+            .map(|(cell, _)| cell)
+    }
+
+    fn cell_at(&self, position: Vec3) -> Cell {
+        (
+            ((position.x - self.origin.x) / self.cell_size).floor() as i32,
+            ((position.z - self.origin.z) / self.cell_size).floor() as i32,
+        )
+    }
+
+    fn cell_center(&self, (x, z): Cell) -> Vec3 {
+        Vec3::new(
+            self.origin.x + (x as f32 + 0.5) * self.cell_size,
+            0.0,
+            self.origin.z + (z as f32 + 0.5) * self.cell_size,
+        )
+    }
+
+    fn height(&self, (x, z): Cell) -> Option<f32> {
+        if x < 0 || z < 0 || x >= self.width || z >= self.depth {
+            return None;
+        }
+
+        self.cells[self.index((x, z))]
+    }
+
+    fn index(&self, (x, z): Cell) -> usize {
+        (z * self.width + x) as usize
+    }
+}
+
+struct OpenCell {
+    estimate: f32,
+    cell: Cell,
+}
+
+// Reversed so the `BinaryHeap` pops the lowest estimate first.
+impl Ord for OpenCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+impl PartialOrd for OpenCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for OpenCell {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl Eq for OpenCell {}
+
+/// Walks a character to `destination` along the navmesh by steering its
+/// `CharacterInput`. The destination is cleared once reached, or right away
+/// when it can't be reached.
+#[derive(Component, Default)]
+pub struct NavAgent {
+    destination: Option<Vec3>,
+    path: Vec<Vec3>,
+    needs_path: bool,
+}
+
+impl NavAgent {
+    pub fn set_destination(&mut self, destination: Option<Vec3>) {
+        self.destination = destination;
+        self.needs_path = true;
+    }
+
+    pub fn destination(&self) -> Option<Vec3> {
+        self.destination
+    }
+}
+
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavMeshSettings>()
+            .init_resource::<NavMesh>()
+            .add_systems(PostUpdate, rebuild_nav_mesh.after(PhysicsSet::Writeback))
+            .add_systems(
+                Update,
+                (find_paths, follow_paths)
+                    .chain()
+                    .in_set(CharacterSet::Navigation),
+            );
+    }
+}
+
+/// Rebuilds the navmesh whenever a static collider or a `NavObstacle` is
+/// added, moved, changed or removed. It runs after the physics step so the
+/// new colliders are already part of the query pipeline.
+#[allow(clippy::too_many_arguments)]
+fn rebuild_nav_mesh(
+    mut nav_mesh: ResMut<NavMesh>,
+    settings: Res<NavMeshSettings>,
+    rapier_context: Res<RapierContext>,
+    level_query: Query<Option<&RigidBody>, (With<Collider>, Without<KinematicCharacterController>)>,
+    obstacle_query: Query<(), With<NavObstacle>>,
+    moved_query: Query<Entity, Changed<GlobalTransform>>,
+    reshaped_query: Query<Entity, Changed<Collider>>,
+    mut removed_colliders: RemovedComponents<Collider>,
+) {
+    // Characters have a collider without a rigid body too, but they move so
+    // they are left out of the level. So are moving platforms, unlike doors.
+    let is_static = |entity| {
+        obstacle_query.contains(entity)
+            || level_query
+                .get(entity)
+                .is_ok_and(|rigid_body| matches!(rigid_body, None | Some(RigidBody::Fixed)))
+    };
+
+    let changed = moved_query
+        .iter()
+        .chain(reshaped_query.iter())
+        .any(is_static)
+        || removed_colliders.read().count() > 0;
+
+    if !changed && !settings.is_changed() {
+        return;
+    }
+
+    let filter = QueryFilter::default()
+        .exclude_sensors()
+        .predicate(&is_static);
+
+    *nav_mesh = NavMesh::build(&rapier_context, &settings, filter);
+}
+
+fn find_paths(mut agent_query: Query<(&mut NavAgent, &Transform)>, nav_mesh: Res<NavMesh>) {
+    for (mut agent, transform) in agent_query.iter_mut() {
+        if !agent.needs_path && !nav_mesh.is_changed() {
+            continue;
+        }
+
+        agent.needs_path = false;
+
+        let Some(destination) = agent.destination else {
+            agent.path.clear();
+            continue;
+        };
+
+        match nav_mesh.find_path(transform.translation, destination) {
+            Some(path) => agent.path = path,
+            None => {
+                agent.destination = None;
+                agent.path.clear();
+            }
+        }
+    }
+}
+
+fn follow_paths(mut agent_query: Query<(&mut NavAgent, &mut CharacterInput, &Transform)>) {
+    for (mut agent, mut character_input, transform) in agent_query.iter_mut() {
+        while let Some(waypoint) = agent.path.first() {
+            if waypoint.xz().distance(transform.translation.xz()) > WAYPOINT_REACHED_DISTANCE {
+                break;
+            }
+
+            agent.path.remove(0);
+        }
+
+        let Some(waypoint) = agent.path.first() else {
+            if agent.destination.is_some() {
+                agent.destination = None;
+            }
+
+            character_input.movement = Vec3::ZERO;
+            continue;
+        };
+
+        let mut direction = *waypoint - transform.translation;
+        direction.y = 0.0;
+        character_input.movement = direction.normalize_or_zero();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_rapier3d::rapier::prelude::ColliderBuilder;
+
+    use super::*;
+
+    /// Physics context with a floor and a box in the middle of it, with no
+    /// app or plugin involved.
+    fn floor_with_box() -> RapierContext {
+        let mut rapier_context = RapierContext::default();
+
+        let cuboids = [
+            (Vec3::new(0.0, -0.1, 0.0), Vec3::new(4.0, 0.1, 4.0)),
+            (Vec3::new(0.0, 0.5, 0.0), Vec3::new(1.0, 0.5, 1.0)),
+        ];
+
+        for (index, (center, half_extents)) in cuboids.into_iter().enumerate() {
+            let collider = ColliderBuilder::cuboid(half_extents.x, half_extents.y, half_extents.z)
+                .translation(center.into())
+                .user_data(Entity::from_raw(index as u32).to_bits() as u128);
+
+            rapier_context.colliders.insert(collider);
+        }
+
+        let RapierContext {
+            query_pipeline,
+            bodies,
+            colliders,
+            ..
+        } = &mut rapier_context;
+        query_pipeline.update(bodies, colliders);
+
+        rapier_context
+    }
+
+    fn inside_box(point: Vec3) -> bool {
+        point.x.abs() < 1.0 && point.z.abs() < 1.0
+    }
+
+    #[test]
+    fn path_goes_around_the_box() {
+        let nav_mesh = NavMesh::build(
+            &floor_with_box(),
+            &NavMeshSettings::default(),
+            QueryFilter::default(),
+        );
+
+        let from = Vec3::new(-3.0, 0.0, 0.0);
+        let to = Vec3::new(3.0, 0.0, 0.0);
+        let path = nav_mesh
+            .find_path(from, to)
+            .expect("no path around the box");
+
+        assert_eq!(path.last().map(|point| point.xz()), Some(to.xz()));
+
+        let mut previous = from;
+        for waypoint in path {
+            for step in 0..=20 {
+                let point = previous.lerp(waypoint, step as f32 / 20.0);
+                assert!(!inside_box(point), "path crosses the box at {point}");
+            }
+
+            previous = waypoint;
+        }
+    }
+
+    #[test]
+    fn no_path_onto_the_box_top() {
+        let nav_mesh = NavMesh::build(
+            &floor_with_box(),
+            &NavMeshSettings::default(),
+            QueryFilter::default(),
+        );
+
+        let top = Vec3::new(0.0, 1.0, 0.0);
+
+        // The top is walkable, it just can't be reached from the floor.
+        assert!(nav_mesh.find_path(top, top + Vec3::X * 0.5).is_some());
+        assert!(nav_mesh.find_path(Vec3::new(-3.0, 0.0, 0.0), top).is_none());
+    }
+}
@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+
+use crate::{navigation::NavAgent, player::character_bundle, schedule::CharacterSet};
+
+const MAX_STROLL_DURATION: f32 = 8.0;
+
+/// AI brain that strolls to random spots around `home`, resting in between.
+#[derive(Component)]
+pub struct Wander {
+    home: Vec3,
+    radius: f32,
+    rest: Timer,
+    stroll_elapsed: f32,
+    seed: u32,
+}
+
+impl Wander {
+    pub fn new(home: Vec3, radius: f32, rest_seconds: f32, seed: u32) -> Wander {
+        Wander {
+            home,
+            radius,
+            rest: Timer::from_seconds(rest_seconds, TimerMode::Once),
+            stroll_elapsed: 0.0,
+            seed,
+        }
+    }
+
+    fn next_destination(&mut self) -> Vec3 {
+        self.seed = self.seed.wrapping_add(1);
+
+        let angle = random(self.seed * 2) * std::f32::consts::TAU;
+        let distance = random(self.seed * 2 + 1).sqrt() * self.radius;
+
+        self.home + Vec3::new(angle.cos(), 0.0, angle.sin()) * distance
+    }
+}
+
+pub struct NpcPlugin;
+
+impl Plugin for NpcPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_npcs)
+            .add_systems(Update, wander.in_set(CharacterSet::Input));
+    }
+}
+
+fn spawn_npcs(mut commands: Commands, assets: Res<AssetServer>) {
+    let npcs = [
+        ("Wanderer 1", Vec3::new(2.5, 0.0, -2.0), 2.0),
+        ("Wanderer 2", Vec3::new(-2.5, 0.0, 1.0), 1.5),
+        ("Wanderer 3", Vec3::new(-0.5, 0.0, 2.0), 1.0),
+    ];
+
+    for (index, (name, home, rest_seconds)) in npcs.into_iter().enumerate() {
+        let npc = commands.spawn_empty().id();
+
+        commands.entity(npc).insert((
+            character_bundle(&assets, Transform::from_translation(home), npc),
+            Wander::new(home, 1.5, rest_seconds, index as u32 * 1000),
+            NavAgent::default(),
+            Name::new(name),
+        ));
+    }
+}
+
+fn wander(mut npc_query: Query<(&mut Wander, &mut NavAgent)>, time: Res<Time>) {
+    for (mut wander, mut nav_agent) in npc_query.iter_mut() {
+        // Strolls end on arrival, when the spot is unreachable, or when the
+        // NPC got stuck for too long.
+        if nav_agent.destination().is_some() {
+            wander.stroll_elapsed += time.delta_seconds();
+
+            if wander.stroll_elapsed > MAX_STROLL_DURATION {
+                nav_agent.set_destination(None);
+            }
+
+            continue;
+        }
+
+        if wander.rest.tick(time.delta()).finished() {
+            let destination = wander.next_destination();
+            nav_agent.set_destination(Some(destination));
+
+            wander.rest.reset();
+            wander.stroll_elapsed = 0.0;
+        }
+    }
+}
+
+/// Deterministic pseudo random number in the `[0, 1]` range.
+fn random(seed: u32) -> f32 {
+    let mut x = seed.wrapping_mul(0x9E37_79B9);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x2C1B_3C6D);
+    x ^= x >> 12;
+
+    x as f32 / u32::MAX as f32
+}
@@ -0,0 +1,226 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    local_player::PlayerInput,
+    schedule::{CharacterSet, InGameSet},
+};
+
+const EYE_HEIGHT: f32 = 1.6;
+const CHEST_HEIGHT: f32 = 1.0;
+const SUSPICIOUS_AWARENESS: f32 = 0.3;
+const CALM_DOWN_AWARENESS: f32 = 0.5;
+const HEARING_AWARENESS: f32 = 0.4;
+const MARKER_HEIGHT: f32 = 2.1;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Reflect)]
+pub enum AwarenessLevel {
+    Unaware,
+    Suspicious,
+    Alerted,
+}
+
+/// Something that can be heard within `loudness` times the hearing radius
+/// of whoever listens.
+#[derive(Event)]
+pub struct Noise {
+    pub position: Vec3,
+    pub loudness: f32,
+}
+
+pub struct PerceptionDescriptor {
+    /// Full angle of the vision cone in radians.
+    pub fov: f32,
+    pub sight_range: f32,
+    pub hearing_radius: f32,
+    /// Awareness gained per second with the player in sight.
+    pub gain_rate: f32,
+    /// Awareness lost per second without.
+    pub decay_rate: f32,
+}
+
+/// Lets a character notice players it sees inside its field of view or hears
+/// through `Noise` events. Awareness fills up while a player is noticed and
+/// slowly drains otherwise.
+#[derive(Component, Reflect)]
+pub struct Perception {
+    fov: f32,
+    sight_range: f32,
+    hearing_radius: f32,
+    gain_rate: f32,
+    decay_rate: f32,
+    awareness: f32,
+    level: AwarenessLevel,
+    target: Option<Entity>,
+    last_known_position: Option<Vec3>,
+}
+
+impl Perception {
+    pub fn new(descriptor: PerceptionDescriptor) -> Perception {
+        Perception {
+            fov: descriptor.fov,
+            sight_range: descriptor.sight_range,
+            hearing_radius: descriptor.hearing_radius,
+            gain_rate: descriptor.gain_rate,
+            decay_rate: descriptor.decay_rate,
+            awareness: 0.0,
+            level: AwarenessLevel::Unaware,
+            target: None,
+            last_known_position: None,
+        }
+    }
+
+    pub fn level(&self) -> AwarenessLevel {
+        self.level
+    }
+
+    /// The player currently in sight, if any.
+    pub fn target(&self) -> Option<Entity> {
+        self.target
+    }
+
+    /// Where a player was last seen or heard.
+    pub fn last_known_position(&self) -> Option<Vec3> {
+        self.last_known_position
+    }
+
+    fn raise(&mut self, amount: f32) {
+        self.awareness = (self.awareness + amount).min(1.0);
+    }
+}
+
+pub struct PerceptionPlugin;
+
+impl Plugin for PerceptionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Noise>()
+            .add_systems(
+                Update,
+                (update_perception, update_awareness_level)
+                    .chain()
+                    .in_set(InGameSet::EntityUpdates)
+                    .before(CharacterSet::Input),
+            )
+            .add_systems(Update, draw_awareness_markers)
+            .register_type::<Perception>();
+    }
+}
+
+fn update_perception(
+    mut perception_query: Query<(Entity, &mut Perception, &GlobalTransform)>,
+    // Cameras and camera targets share `LocalPlayer`, only the characters
+    // themselves read `PlayerInput`.
+    player_query: Query<(Entity, &GlobalTransform), With<PlayerInput>>,
+    mut noise_events: EventReader<Noise>,
+    rapier_context: Res<RapierContext>,
+    time: Res<Time>,
+) {
+    let noises = noise_events.read().collect::<Vec<_>>();
+
+    for (entity, mut perception, transform) in perception_query.iter_mut() {
+        let eye = transform.translation() + Vec3::Y * EYE_HEIGHT;
+        // Characters face their back, see `move_player`.
+        let facing = transform.back();
+
+        let seen = player_query
+            .iter()
+            .filter_map(|(player, player_transform)| {
+                let chest = player_transform.translation() + Vec3::Y * CHEST_HEIGHT;
+                let to_player = chest - eye;
+                let distance = to_player.length();
+
+                if distance > perception.sight_range
+                    || facing.angle_between(to_player) > perception.fov * 0.5
+                {
+                    return None;
+                }
+
+                let (hit, _) = rapier_context.cast_ray(
+                    eye,
+                    to_player / distance,
+                    distance,
+                    true,
+                    QueryFilter::default()
+                        .exclude_collider(entity)
+                        .exclude_sensors(),
+                )?;
+
+                (hit == player).then_some((player, player_transform.translation(), distance))
+            })
+            .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b));
+
+        perception.target = seen.map(|(player, _, _)| player);
+
+        if let Some((_, position, distance)) = seen {
+            // Closer players are noticed faster.
+            let closeness = 1.0 - 0.5 * distance / perception.sight_range;
+            let gain = perception.gain_rate * closeness * time.delta_seconds();
+
+            perception.raise(gain);
+            perception.last_known_position = Some(position);
+            continue;
+        }
+
+        let position = transform.translation();
+        let heard = noises
+            .iter()
+            .filter(|noise| {
+                noise.position.distance(position) <= perception.hearing_radius * noise.loudness
+            })
+            .max_by(|a, b| a.loudness.total_cmp(&b.loudness));
+
+        match heard {
+            Some(noise) => {
+                perception.raise(HEARING_AWARENESS * noise.loudness);
+                perception.last_known_position = Some(noise.position);
+            }
+            None => {
+                let decay = perception.decay_rate * time.delta_seconds();
+                perception.awareness = (perception.awareness - decay).max(0.0);
+            }
+        }
+    }
+}
+
+/// A full awareness bar alerts the character, it only calms down again once
+/// the bar drained to half so it doesn't flicker while losing sight.
+fn update_awareness_level(mut perception_query: Query<&mut Perception>) {
+    for mut perception in perception_query.iter_mut() {
+        let level = match (perception.level, perception.awareness) {
+            (_, awareness) if awareness >= 1.0 => AwarenessLevel::Alerted,
+            (AwarenessLevel::Alerted, awareness) if awareness >= CALM_DOWN_AWARENESS => {
+                AwarenessLevel::Alerted
+            }
+            (_, awareness) if awareness >= SUSPICIOUS_AWARENESS => AwarenessLevel::Suspicious,
+            _ => AwarenessLevel::Unaware,
+        };
+
+        if level == AwarenessLevel::Unaware {
+            perception.last_known_position = None;
+        }
+
+        perception.level = level;
+    }
+}
+
+fn draw_awareness_markers(
+    mut gizmos: Gizmos,
+    perception_query: Query<(&Perception, &GlobalTransform)>,
+) {
+    for (perception, transform) in perception_query.iter() {
+        let color = match perception.level {
+            AwarenessLevel::Unaware => continue,
+            AwarenessLevel::Suspicious => Color::YELLOW,
+            AwarenessLevel::Alerted => Color::RED,
+        };
+
+        let position = transform.translation() + Vec3::Y * MARKER_HEIGHT;
+        gizmos.line(
+            position,
+            position + Vec3::Y * 0.25 * perception.awareness,
+            color,
+        );
+        gizmos.sphere(position, Quat::IDENTITY, 0.05, color);
+    }
+}
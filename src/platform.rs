@@ -0,0 +1,252 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    player::GroundMotion,
+    schedule::{CharacterSet, InGameSet, SignalSet},
+    wiring::{SignalInput, Signals},
+};
+
+const GROUND_PROBE_HEIGHT: f32 = 0.1;
+const GROUND_PROBE_DISTANCE: f32 = 0.3;
+
+#[derive(Deserialize, Clone)]
+pub enum PlatformShape {
+    /// Half extents of a box.
+    Box(Vec3),
+    Disc {
+        radius: f32,
+        height: f32,
+    },
+}
+
+#[derive(Deserialize, Clone)]
+pub enum PlatformMotion {
+    /// Slides to `to` and back at `speed` meters per second.
+    PingPong { to: Vec3, speed: f32 },
+    /// Loops through the waypoints at `speed` meters per second, returning to
+    /// where it started after the last one.
+    Path { waypoints: Vec<Vec3>, speed: f32 },
+    /// Spins around its vertical axis at `speed` radians per second.
+    Rotating { speed: f32 },
+}
+
+/// Layout of a platform in a level file, with a `signal` it only moves while
+/// that signal is on.
+#[derive(Deserialize)]
+pub struct PlatformDefinition {
+    pub name: String,
+    pub shape: PlatformShape,
+    pub position: Vec3,
+    pub motion: PlatformMotion,
+    #[serde(default)]
+    pub signal: Option<String>,
+}
+
+/// Kinematic body following its `PlatformMotion`, characters standing on it
+/// are carried along.
+#[derive(Component)]
+pub struct Platform {
+    motion: PlatformMotion,
+    origin: Vec3,
+    elapsed: f32,
+    previous_translation: Vec3,
+    translation: Vec3,
+    rotation_delta: Quat,
+}
+
+impl Platform {
+    pub fn new(motion: PlatformMotion, origin: Vec3) -> Platform {
+        Platform {
+            motion,
+            origin,
+            elapsed: 0.0,
+            previous_translation: origin,
+            translation: origin,
+            rotation_delta: Quat::IDENTITY,
+        }
+    }
+
+    /// How far a point riding the platform moved during its last step, and
+    /// how much it turned.
+    pub fn carry(&self, point: Vec3) -> (Vec3, Quat) {
+        let carried = self.translation + self.rotation_delta * (point - self.previous_translation);
+
+        (carried - point, self.rotation_delta)
+    }
+
+    fn translation_at(&self, elapsed: f32) -> Vec3 {
+        match &self.motion {
+            PlatformMotion::PingPong { to, speed } => {
+                let length = self.origin.distance(*to);
+                if length == 0.0 {
+                    return self.origin;
+                }
+
+                let travelled = (speed * elapsed).rem_euclid(2.0 * length);
+                let progress = length - (travelled - length).abs();
+
+                self.origin.lerp(*to, progress / length)
+            }
+            PlatformMotion::Path { waypoints, speed } => {
+                let points = std::iter::once(self.origin)
+                    .chain(waypoints.iter().copied())
+                    .collect::<Vec<_>>();
+                let segments = points
+                    .iter()
+                    .zip(points.iter().cycle().skip(1))
+                    .map(|(from, to)| (*from, *to, from.distance(*to)));
+                let length = segments.clone().map(|(_, _, length)| length).sum::<f32>();
+                if length == 0.0 {
+                    return self.origin;
+                }
+
+                let mut travelled = (speed * elapsed).rem_euclid(length);
+                for (from, to, length) in segments {
+                    if travelled <= length {
+                        return from.lerp(to, travelled / length);
+                    }
+
+                    travelled -= length;
+                }
+
+                self.origin
+            }
+            PlatformMotion::Rotating { .. } => self.origin,
+        }
+    }
+
+    fn rotation_at(&self, elapsed: f32) -> Quat {
+        match &self.motion {
+            PlatformMotion::Rotating { speed } => Quat::from_rotation_y(speed * elapsed),
+            _ => Quat::IDENTITY,
+        }
+    }
+}
+
+pub struct PlatformPlugin;
+
+impl Plugin for PlatformPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (move_platforms, ride_platforms)
+                .chain()
+                .in_set(InGameSet::EntityUpdates)
+                .after(SignalSet::Evaluate)
+                .before(CharacterSet::Controller),
+        );
+    }
+}
+
+/// Everything a platform described in a level file needs.
+pub fn platform_bundle(
+    definition: &PlatformDefinition,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    assets: &AssetServer,
+) -> impl Bundle {
+    let (mesh, collider) = match definition.shape {
+        PlatformShape::Box(half_extents) => (
+            meshes.add(Cuboid::from_size(half_extents * 2.0)),
+            Collider::cuboid(half_extents.x, half_extents.y, half_extents.z),
+        ),
+        PlatformShape::Disc { radius, height } => (
+            meshes.add(Cylinder::new(radius, height)),
+            Collider::cylinder(height * 0.5, radius),
+        ),
+    };
+
+    (
+        PbrBundle {
+            mesh,
+            material: materials.add(StandardMaterial {
+                base_color_texture: Some(assets.load("grid_textures/Orange/g1661.png")),
+                ..default()
+            }),
+            transform: Transform::from_translation(definition.position),
+            ..default()
+        },
+        Name::new(definition.name.clone()),
+        Platform::new(definition.motion.clone(), definition.position),
+        RigidBody::KinematicPositionBased,
+        collider,
+    )
+}
+
+fn move_platforms(
+    mut platform_query: Query<(&mut Platform, &mut Transform, Option<&SignalInput>)>,
+    signals: Res<Signals>,
+    time: Res<Time>,
+) {
+    for (mut platform, mut transform, input) in platform_query.iter_mut() {
+        let powered = input.is_none_or(|input| signals.get(&input.0));
+
+        let previous_rotation = platform.rotation_at(platform.elapsed);
+        if powered {
+            platform.elapsed += time.delta_seconds();
+        }
+
+        let translation = platform.translation_at(platform.elapsed);
+        let rotation = platform.rotation_at(platform.elapsed);
+
+        platform.previous_translation = platform.translation;
+        platform.translation = translation;
+        platform.rotation_delta = rotation * previous_rotation.inverse();
+
+        transform.translation = translation;
+        transform.rotation = rotation;
+    }
+}
+
+/// Carries grounded characters with the platform below them. Whoever leaves
+/// a platform keeps its horizontal velocity until landing again, so jumping
+/// off a moving platform doesn't stop dead in the air.
+fn ride_platforms(
+    mut rider_query: Query<(
+        Entity,
+        &Transform,
+        &KinematicCharacterControllerOutput,
+        &mut GroundMotion,
+    )>,
+    platform_query: Query<&Platform>,
+    rapier_context: Res<RapierContext>,
+    time: Res<Time>,
+) {
+    let delta_seconds = time.delta_seconds();
+    if delta_seconds == 0.0 {
+        return;
+    }
+
+    for (entity, transform, output, mut ground_motion) in rider_query.iter_mut() {
+        if !output.grounded {
+            ground_motion.displacement = ground_motion.momentum * delta_seconds;
+            ground_motion.rotation = Quat::IDENTITY;
+            continue;
+        }
+
+        let platform = rapier_context
+            .cast_ray(
+                transform.translation + Vec3::Y * GROUND_PROBE_HEIGHT,
+                Vec3::NEG_Y,
+                GROUND_PROBE_HEIGHT + GROUND_PROBE_DISTANCE,
+                true,
+                QueryFilter::default()
+                    .exclude_collider(entity)
+                    .exclude_sensors(),
+            )
+            .and_then(|(ground, _)| platform_query.get(ground).ok());
+
+        let Some(platform) = platform else {
+            *ground_motion = GroundMotion::default();
+            continue;
+        };
+
+        let (displacement, rotation) = platform.carry(transform.translation);
+
+        ground_motion.displacement = displacement;
+        ground_motion.rotation = rotation;
+        ground_motion.momentum = Vec3::new(displacement.x, 0.0, displacement.z) / delta_seconds;
+    }
+}
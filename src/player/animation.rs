@@ -8,46 +8,50 @@ pub struct PlayerAnimationPlugin;
 
 impl Plugin for PlayerAnimationPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, initialize_animations_resource)
-            .add_systems(Update, update_animation);
+        app.add_systems(Update, update_animation);
     }
 }
 
-#[derive(Resource)]
-struct AnimationHandles(HashMap<PlayerState, Handle<AnimationClip>>);
-
-impl AnimationHandles {
-    pub fn get(&self, animation: &PlayerState) -> Handle<AnimationClip> {
-        self.0.get(animation).unwrap().clone_weak()
+/// The clip a character plays for each of its states.
+#[derive(Component)]
+pub struct AnimationSet(HashMap<PlayerState, Handle<AnimationClip>>);
+
+impl AnimationSet {
+    /// Loads the clips of `gltf` by their index in the file.
+    pub fn load(assets: &AssetServer, gltf: &str, clips: &[(PlayerState, usize)]) -> AnimationSet {
+        AnimationSet(
+            clips
+                .iter()
+                .map(|(state, index)| (*state, assets.load(format!("{gltf}#Animation{index}"))))
+                .collect(),
+        )
     }
 }
 
-fn initialize_animations_resource(mut commands: Commands, assets: Res<AssetServer>) {
-    #[rustfmt::skip]
-    commands.insert_resource(AnimationHandles(HashMap::from([
-        (PlayerState::Idle, assets.load("player.gltf#Animation0")),
-        (PlayerState::Jumping, assets.load("player.gltf#Animation1")),
-        (PlayerState::Runing, assets.load("player.gltf#Animation2")),
-        (PlayerState::Aiming, assets.load("player.gltf#Animation3")),
-        (PlayerState::Sprinting, assets.load("player.gltf#Animation4")),
-        (PlayerState::Walking, assets.load("player.gltf#Animation5")),
-    ])));
-}
+/// Links an `AnimationPlayer` inside a character scene to the character root,
+/// inserted by the scene hook when the scene spawns.
+#[derive(Component)]
+pub struct AnimationOwner(pub Entity);
 
 fn update_animation(
-    mut animation_players: Query<&mut AnimationPlayer>,
-    animations: Res<AnimationHandles>,
-    player_state_query: Query<&PlayerState>,
+    mut animation_players: Query<(&AnimationOwner, &mut AnimationPlayer)>,
+    character_query: Query<(&PlayerState, &AnimationSet)>,
 ) {
-    let player_state = player_state_query.single();
+    for (owner, mut player) in &mut animation_players {
+        let Ok((player_state, animation_set)) = character_query.get(owner.0) else {
+            continue;
+        };
+
+        let Some(animation) = animation_set.0.get(player_state) else {
+            continue;
+        };
 
-    for mut player in &mut animation_players {
         match player_state {
             PlayerState::Aiming => player
-                .play_with_transition(animations.get(player_state), Duration::from_millis(100))
+                .play_with_transition(animation.clone_weak(), Duration::from_millis(100))
                 .seek_to(0.5),
             _ => player
-                .play_with_transition(animations.get(player_state), Duration::from_millis(100))
+                .play_with_transition(animation.clone_weak(), Duration::from_millis(100))
                 .repeat(),
         };
     }
@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+
+use super::controller::{CharacterInput, PlayerState, VerticalSpeed};
+use crate::{interaction::Interacted, ladder::Ladder};
+
+const MOUNT_RANGE: f32 = 0.8;
+const MOUNT_WIDTH: f32 = 0.5;
+const MOUNT_TOLERANCE: f32 = 0.3;
+/// How far below the top a character mounting from above starts climbing.
+const TOP_MOUNT_DROP: f32 = 1.0;
+const LADDER_DISTANCE: f32 = 0.35;
+const CLIMB_SPEED: f32 = 1.2;
+const DISMOUNT_DEPTH: f32 = 0.5;
+
+/// The ladder a character is attached to, and how far up it the feet are.
+#[derive(Component, Default)]
+pub struct LadderClimber {
+    ladder: Option<Entity>,
+    height: f32,
+}
+
+impl LadderClimber {
+    pub fn attached(&self) -> bool {
+        self.ladder.is_some()
+    }
+}
+
+/// Where a character standing at `position` would start climbing `ladder`,
+/// from its bottom or, standing on top of it, from above.
+fn mount_height(ladder: &Ladder, position: Vec3) -> Option<f32> {
+    let offset = position - ladder.bottom;
+    let along = offset.dot(ladder.facing);
+    let across = (offset - ladder.facing * along - Vec3::Y * offset.y).length();
+
+    if across > MOUNT_WIDTH {
+        return None;
+    }
+
+    let at_top = (offset.y - ladder.height).abs() < MOUNT_TOLERANCE;
+    if at_top && (-MOUNT_RANGE..=0.0).contains(&along) {
+        return Some(ladder.height - TOP_MOUNT_DROP);
+    }
+
+    let in_front = (0.0..=MOUNT_RANGE).contains(&along);
+    let beside = (-MOUNT_TOLERANCE..=ladder.height).contains(&offset.y);
+    (in_front && beside).then_some(offset.y.max(0.0))
+}
+
+/// Attaches characters to the ladder they interact with when standing at
+/// either end of it, interacting with it again lets go.
+pub(super) fn mount_ladders(
+    mut character_query: Query<(&Transform, &mut PlayerState, &mut LadderClimber)>,
+    ladder_query: Query<&Ladder>,
+    mut interacted_events: EventReader<Interacted>,
+) {
+    for event in interacted_events.read() {
+        let Ok(ladder) = ladder_query.get(event.entity) else {
+            continue;
+        };
+
+        let Ok((transform, mut player_state, mut climber)) =
+            character_query.get_mut(event.interactor)
+        else {
+            continue;
+        };
+
+        if matches!(
+            *player_state,
+            PlayerState::Hanging | PlayerState::Climbing | PlayerState::Dodging
+        ) {
+            continue;
+        }
+
+        if climber.attached() {
+            climber.ladder = None;
+            *player_state = PlayerState::Jumping;
+            continue;
+        }
+
+        if let Some(height) = mount_height(ladder, transform.translation) {
+            climber.ladder = Some(event.entity);
+            climber.height = height;
+            *player_state = PlayerState::LadderClimbing;
+        }
+    }
+}
+
+/// Moves attached characters up and down the ladder axis instead of across
+/// the ground, stepping off onto the top or the floor at either end. Jumping
+/// lets go.
+pub(super) fn climb_ladders(
+    mut character_query: Query<(
+        &mut Transform,
+        &CharacterInput,
+        &mut VerticalSpeed,
+        &mut PlayerState,
+        &mut LadderClimber,
+    )>,
+    ladder_query: Query<&Ladder>,
+    time: Res<Time>,
+) {
+    for (mut transform, input, mut vertical_speed, mut player_state, mut climber) in
+        character_query.iter_mut()
+    {
+        let Some(entity) = climber.ladder else {
+            continue;
+        };
+
+        let Ok(ladder) = ladder_query.get(entity) else {
+            climber.ladder = None;
+            *player_state = PlayerState::Jumping;
+            continue;
+        };
+
+        vertical_speed.0 = 0.0;
+
+        if input.jump {
+            climber.ladder = None;
+            *player_state = PlayerState::Jumping;
+            continue;
+        }
+
+        // Pushing towards the ladder climbs up, pulling away climbs down.
+        let climb = input.movement.dot(-ladder.facing);
+        climber.height += climb * CLIMB_SPEED * time.delta_seconds();
+
+        if climber.height >= ladder.height {
+            transform.translation =
+                ladder.bottom + Vec3::Y * ladder.height - ladder.facing * DISMOUNT_DEPTH;
+            climber.ladder = None;
+            *player_state = PlayerState::Idle;
+            continue;
+        }
+
+        if climber.height <= 0.0 && climb < 0.0 {
+            transform.translation = ladder.bottom + ladder.facing * LADDER_DISTANCE;
+            climber.ladder = None;
+            *player_state = PlayerState::Idle;
+            continue;
+        }
+
+        climber.height = climber.height.max(0.0);
+        transform.translation =
+            ladder.bottom + ladder.facing * LADDER_DISTANCE + Vec3::Y * climber.height;
+        transform.look_to(ladder.facing, Vec3::Y);
+    }
+}
@@ -1,15 +1,34 @@
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 
-use crate::{camera_controller::CameraController, schedule::InGameSet};
+use super::climbing::{climb_ladders, mount_ladders, LadderClimber};
+use super::dodge::{perform_dodges, start_dodges, Dodge};
+use super::ledge::{grab_ledges, hang_from_ledges, LedgeGrab};
+use super::swimming::{swim, update_swimming, Swimmer};
+use crate::{
+    camera_controller::CameraController,
+    camera_mode::free_fly_active,
+    camera_shake::AddTrauma,
+    cutscene::cutscene_playing,
+    local_player::{LocalPlayer, PlayerAction, PlayerInput},
+    lock_on::{LockOnTarget, Targetable},
+    perception::Noise,
+    schedule::{CharacterSet, InGameSet},
+};
 
+/// World space direction the character moves towards on the ground plane.
 #[derive(Component)]
 pub struct MovementDirection(pub Vec3);
 
 #[derive(Component)]
 pub struct VerticalSpeed(pub f32);
 
-#[derive(Component, PartialEq, Eq, Hash, Debug)]
+/// Whether the character was grounded last frame, to tell landings apart
+/// from standing on the ground.
+#[derive(Component, Default)]
+pub struct Grounded(pub bool);
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum PlayerState {
     Idle,
     Walking,
@@ -17,6 +36,67 @@ pub enum PlayerState {
     Sprinting,
     Jumping,
     Aiming,
+    Sliding,
+    Crouching,
+    CrouchWalking,
+    Hanging,
+    Climbing,
+    Dodging,
+    Swimming,
+    LadderClimbing,
+}
+
+/// What a character wants to do this frame. It is written by the character's
+/// input source, local player input or an AI brain, and turned into a
+/// `PlayerState` by the shared character state system.
+#[derive(Component, Default)]
+pub struct CharacterInput {
+    /// World space movement on the ground plane, at most one unit long.
+    pub movement: Vec3,
+    /// Up or down movement where the character isn't bound to the ground,
+    /// like while swimming, between -1 and 1.
+    pub vertical: f32,
+    /// Direction to face instead of the movement direction, like the camera
+    /// forward while aiming or a lock-on target.
+    pub look: Option<Vec3>,
+    pub jump: bool,
+    pub run: bool,
+    pub aim: bool,
+    pub crouch: bool,
+    /// Only set on the frame the dodge is asked for, holding the button
+    /// doesn't chain dodges.
+    pub dodge: bool,
+}
+
+/// Whether the character is crouched, it stays crouched after letting go
+/// until there is room to stand up.
+#[derive(Component, Default)]
+pub struct Crouched(pub bool);
+
+/// Normal of the ground below a character when it is too steep to stand on.
+#[derive(Component)]
+pub struct Slope(pub Option<Vec3>);
+
+/// Movement a character picks up from what it stands on, like a moving
+/// platform, on top of its own.
+#[derive(Component, Default)]
+pub struct GroundMotion {
+    /// How far the ground carries the character this frame.
+    pub displacement: Vec3,
+    /// How much the ground turns the character this frame.
+    pub rotation: Quat,
+    /// Velocity kept from the ground after leaving it, until landing again.
+    pub momentum: Vec3,
+}
+
+/// Makes noise every `interval` while walking or running.
+#[derive(Component)]
+pub struct Footsteps(Timer);
+
+impl Footsteps {
+    pub fn new(interval: f32) -> Footsteps {
+        Footsteps(Timer::from_seconds(interval, TimerMode::Repeating))
+    }
 }
 
 #[derive(Bundle)]
@@ -24,6 +104,15 @@ pub struct PlayerControllerBundle {
     pub initial_state: PlayerState,
     pub movement_direction: MovementDirection,
     pub initial_vertical_speed: VerticalSpeed,
+    pub grounded: Grounded,
+    pub character_input: CharacterInput,
+    pub ground_motion: GroundMotion,
+    pub slope: Slope,
+    pub crouched: Crouched,
+    pub ledge_grab: LedgeGrab,
+    pub dodge: Dodge,
+    pub swimmer: Swimmer,
+    pub ladder_climber: LadderClimber,
     pub collider: Collider,
     pub kinematic_character_controller: KinematicCharacterController,
 }
@@ -32,63 +121,287 @@ pub struct PlayerControllerPlugin;
 
 impl Plugin for PlayerControllerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, update_player_state.in_set(InGameSet::UserInput))
+        app.add_systems(Update, read_local_input.in_set(InGameSet::UserInput))
+            .add_systems(
+                Update,
+                release_player_input
+                    .run_if(free_fly_active.or_else(cutscene_playing))
+                    .in_set(CharacterSet::Input),
+            )
             .add_systems(
                 Update,
-                (apply_gravity, move_player)
+                (
+                    detect_steep_slopes,
+                    update_crouch,
+                    grab_ledges,
+                    mount_ladders,
+                    update_swimming,
+                    start_dodges,
+                    update_character_state,
+                    emit_landings,
+                    apply_gravity,
+                    move_player,
+                    perform_dodges,
+                    swim,
+                    climb_ladders,
+                    hang_from_ledges,
+                    slide_down_slopes,
+                    emit_footsteps,
+                )
                     .chain()
-                    .in_set(InGameSet::EntityUpdates),
+                    .in_set(CharacterSet::Controller),
             );
     }
 }
 
 const WALKING_SPEED: f32 = 1.8;
 const RUNNING_SPEED: f32 = 4.0;
+const SLIDING_SPEED: f32 = 4.5;
+const CROUCHING_SPEED: f32 = 0.9;
+const CROUCHING_LOUDNESS: f32 = 0.1;
+const CAPSULE_RADIUS: f32 = 0.3;
+const STANDING_CAPSULE_TOP: f32 = 1.5;
+const CROUCHING_CAPSULE_TOP: f32 = 0.8;
+const SLOPE_PROBE_HEIGHT: f32 = 0.1;
+const SLOPE_PROBE_DISTANCE: f32 = 0.5;
+const WALKING_LOUDNESS: f32 = 0.3;
+const RUNNING_LOUDNESS: f32 = 0.6;
 
-fn update_player_state(
+/// Input source of the local players, movement is relative to their camera,
+/// or to their lock-on target while locked on.
+fn read_local_input(
     mut player_query: Query<(
-        &mut PlayerState,
-        &KinematicCharacterControllerOutput,
-        &mut MovementDirection,
+        &mut CharacterInput,
+        &PlayerInput,
+        &LocalPlayer,
+        &LockOnTarget,
+        &Transform,
+    )>,
+    camera_query: Query<(&LocalPlayer, &Transform, &CameraController), Without<CharacterInput>>,
+    targetable_query: Query<&GlobalTransform, With<Targetable>>,
+) {
+    for (mut character_input, player_input, local_player, lock_on_target, player_transform) in
+        player_query.iter_mut()
+    {
+        let Some((_, camera_transform, _)) = camera_query
+            .iter()
+            .find(|(owner, _, _)| *owner == local_player)
+        else {
+            continue;
+        };
+
+        // A camera looking straight down, like the top-down mode, has no
+        // horizontal forward so its up vector is used instead.
+        let camera_pitch = camera_transform.forward().y;
+        let mut camera_forward = *camera_transform.forward();
+        camera_forward.y = 0.0;
+        if camera_forward.length_squared() < 1e-4 {
+            camera_forward = *camera_transform.up();
+        }
+
+        let lock_on_position = lock_on_target
+            .0
+            .and_then(|target| targetable_query.get(target).ok())
+            .map(|transform| transform.translation());
+
+        // While locked on, movement is relative to the target so the player
+        // strafes around it instead of following the camera.
+        let mut forward = match lock_on_position {
+            Some(lock_on_position) => lock_on_position - player_transform.translation,
+            None => camera_forward,
+        };
+        forward.y = 0.0;
+        let forward = forward.normalize_or_zero();
+        let right = forward.cross(Vec3::Y);
+
+        let actions = &player_input.actions;
+
+        character_input.movement =
+            forward * player_input.movement.y + right * player_input.movement.x;
+        character_input.jump = actions.pressed(PlayerAction::Jump);
+        character_input.run = actions.pressed(PlayerAction::Run);
+        character_input.aim = actions.pressed(PlayerAction::Aim);
+        character_input.crouch = actions.pressed(PlayerAction::Crouch);
+        character_input.dodge = actions.just_pressed(PlayerAction::Dodge);
+        // Swimming follows the camera pitch, jump rises and crouch dives.
+        let rise = match (character_input.jump, character_input.crouch) {
+            (true, false) => 1.0,
+            (false, true) => -1.0,
+            _ => 0.0,
+        };
+        character_input.vertical = (camera_pitch * player_input.movement.y + rise).clamp(-1.0, 1.0);
+        character_input.look = match lock_on_position.is_some() || character_input.aim {
+            true => Some(forward),
+            false => None,
+        };
+    }
+}
+
+/// Player input is frozen while the debug camera flies around or a cutscene
+/// plays, so drop whatever was held when it took over instead of walking on
+/// forever.
+fn release_player_input(mut player_query: Query<&mut CharacterInput, With<LocalPlayer>>) {
+    for mut character_input in player_query.iter_mut() {
+        *character_input = CharacterInput::default();
+    }
+}
+
+/// Looks for ground steeper than the controller can climb below each
+/// character, which it then slides down.
+fn detect_steep_slopes(
+    mut character_query: Query<(
+        Entity,
+        &Transform,
+        &KinematicCharacterController,
+        &mut Slope,
     )>,
-    keys: Res<ButtonInput<KeyCode>>,
-    mouse: Res<ButtonInput<MouseButton>>,
+    rapier_context: Res<RapierContext>,
 ) {
-    for (mut player_state, controller, mut movement_direction) in player_query.iter_mut() {
-        let mut direction = Vec3::ZERO;
+    for (entity, transform, controller, mut slope) in character_query.iter_mut() {
+        slope.0 = rapier_context
+            .cast_ray_and_get_normal(
+                transform.translation + Vec3::Y * SLOPE_PROBE_HEIGHT,
+                Vec3::NEG_Y,
+                SLOPE_PROBE_HEIGHT + SLOPE_PROBE_DISTANCE,
+                true,
+                QueryFilter::default()
+                    .exclude_collider(entity)
+                    .exclude_sensors(),
+            )
+            .map(|(_, intersection)| intersection.normal)
+            .filter(|normal| normal.angle_between(Vec3::Y) > controller.max_slope_climb_angle);
+    }
+}
 
-        if keys.pressed(KeyCode::KeyW) {
-            direction += Vec3::X;
+/// Collider of a character, shorter while crouched.
+pub fn character_capsule(crouched: bool) -> Collider {
+    let top = match crouched {
+        true => CROUCHING_CAPSULE_TOP,
+        false => STANDING_CAPSULE_TOP,
+    };
+
+    Collider::capsule(
+        Vec3::new(0.0, CAPSULE_RADIUS, 0.0),
+        Vec3::new(0.0, top, 0.0),
+        CAPSULE_RADIUS,
+    )
+}
+
+/// Shrinks the capsule of characters that want to crouch, and only grows it
+/// back once a shape-cast finds no ceiling in the way.
+fn update_crouch(
+    mut character_query: Query<(
+        Entity,
+        &Transform,
+        &CharacterInput,
+        &mut Crouched,
+        &mut Collider,
+    )>,
+    rapier_context: Res<RapierContext>,
+) {
+    for (entity, transform, character_input, mut crouched, mut collider) in
+        character_query.iter_mut()
+    {
+        if character_input.crouch == crouched.0 {
+            continue;
         }
 
-        if keys.pressed(KeyCode::KeyS) {
-            direction -= Vec3::X;
+        if crouched.0 {
+            let headroom = STANDING_CAPSULE_TOP - CROUCHING_CAPSULE_TOP;
+            let ceiling = rapier_context.cast_shape(
+                transform.translation,
+                transform.rotation,
+                Vec3::Y,
+                &collider,
+                headroom,
+                false,
+                QueryFilter::default()
+                    .exclude_collider(entity)
+                    .exclude_sensors(),
+            );
+
+            if ceiling.is_some() {
+                continue;
+            }
         }
 
-        if keys.pressed(KeyCode::KeyD) {
-            direction += Vec3::Z;
+        crouched.0 = character_input.crouch;
+        *collider = character_capsule(crouched.0);
+    }
+}
+
+fn update_character_state(
+    mut character_query: Query<(
+        &mut PlayerState,
+        &KinematicCharacterControllerOutput,
+        &mut MovementDirection,
+        &CharacterInput,
+        &Slope,
+        &Crouched,
+        &LedgeGrab,
+    )>,
+) {
+    for (
+        mut player_state,
+        controller,
+        mut movement_direction,
+        character_input,
+        slope,
+        crouched,
+        ledge_grab,
+    ) in character_query.iter_mut()
+    {
+        movement_direction.0 = character_input.movement;
+
+        if ledge_grab.hanging() {
+            *player_state = match ledge_grab.climbing() {
+                true => PlayerState::Climbing,
+                false => PlayerState::Hanging,
+            };
+            continue;
         }
 
-        if keys.pressed(KeyCode::KeyA) {
-            direction -= Vec3::Z;
+        // Dodging, swimming and ladders own the state while they last, see
+        // `perform_dodges`, `update_swimming` and `mount_ladders`.
+        if matches!(
+            *player_state,
+            PlayerState::Dodging | PlayerState::Swimming | PlayerState::LadderClimbing
+        ) {
+            continue;
         }
 
-        movement_direction.0 = direction;
+        // The controller doesn't count steep slopes as ground, so sliding
+        // has to be checked before giving up on airborne characters.
+        if slope.0.is_some() {
+            *player_state = PlayerState::Sliding;
+            continue;
+        }
 
         if !controller.grounded {
-            return;
+            if matches!(
+                *player_state,
+                PlayerState::Sliding | PlayerState::Hanging | PlayerState::Climbing
+            ) {
+                *player_state = PlayerState::Jumping;
+            }
+
+            continue;
         }
 
-        if keys.pressed(KeyCode::Space) {
+        if character_input.jump && !crouched.0 {
             *player_state = PlayerState::Jumping;
-        } else if direction != Vec3::ZERO {
-            if keys.pressed(KeyCode::ShiftLeft) {
+        } else if character_input.movement != Vec3::ZERO {
+            if crouched.0 {
+                *player_state = PlayerState::CrouchWalking;
+            } else if character_input.run {
                 *player_state = PlayerState::Runing;
             } else {
                 *player_state = PlayerState::Walking;
             }
-        } else if mouse.pressed(MouseButton::Right) {
+        } else if character_input.aim {
             *player_state = PlayerState::Aiming;
+        } else if crouched.0 {
+            *player_state = PlayerState::Crouching;
         } else {
             *player_state = PlayerState::Idle;
         }
@@ -101,61 +414,189 @@ fn move_player(
         &mut Transform,
         &PlayerState,
         &MovementDirection,
-        &mut VerticalSpeed,
+        &VerticalSpeed,
+        &CharacterInput,
+        &GroundMotion,
     )>,
-    camera_query: Query<
-        &Transform,
-        (
-            With<CameraController>,
-            Without<KinematicCharacterController>,
-        ),
-    >,
-
     time: Res<Time>,
 ) {
-    let camera_transform = camera_query
-        .get_single()
-        .expect("There should be one and only one camera with a CameraController");
-
-    for (mut controller, mut player_transform, player_state, movement_direction, vertical_speed) in
-        controller_query.iter_mut()
+    for (
+        mut controller,
+        mut transform,
+        player_state,
+        movement_direction,
+        vertical_speed,
+        character_input,
+        ground_motion,
+    ) in controller_query.iter_mut()
     {
-        let mut forward = player_transform.translation - camera_transform.translation;
-        forward.y = 0.0;
-        let forward = forward.normalize();
-        let right = forward.cross(Vec3::Y);
+        if matches!(
+            player_state,
+            PlayerState::Sliding
+                | PlayerState::Hanging
+                | PlayerState::Climbing
+                | PlayerState::Dodging
+                | PlayerState::Swimming
+                | PlayerState::LadderClimbing
+        ) {
+            continue;
+        }
 
         let speed = match player_state {
             PlayerState::Walking => WALKING_SPEED,
             PlayerState::Runing => RUNNING_SPEED,
+            PlayerState::CrouchWalking => CROUCHING_SPEED,
             _ => 0.0,
         };
 
-        let direction = forward * movement_direction.0.x + right * movement_direction.0.z;
+        let direction = movement_direction.0;
         let vertical_movement = Vec3::Y * vertical_speed.0 * time.delta_seconds();
 
         let movement = direction * speed * time.delta_seconds();
-        controller.translation = Some(movement + vertical_movement);
-
-        match player_state {
-            PlayerState::Aiming => player_transform.look_to(-forward, Vec3::Y),
-            _ => {
-                if movement_direction.0 != Vec3::ZERO {
-                    player_transform.look_to(-direction, Vec3::Y);
-                }
-            }
+        controller.translation = Some(movement + vertical_movement + ground_motion.displacement);
+
+        transform.rotation = ground_motion.rotation * transform.rotation;
+        let facing = character_input.look.unwrap_or(direction);
+        if facing != Vec3::ZERO {
+            transform.look_to(-facing, Vec3::Y);
+        }
+    }
+}
+
+fn slide_down_slopes(
+    mut controller_query: Query<(
+        &mut KinematicCharacterController,
+        &mut Transform,
+        &PlayerState,
+        &Slope,
+        &GroundMotion,
+    )>,
+    time: Res<Time>,
+) {
+    for (mut controller, mut transform, player_state, slope, ground_motion) in
+        controller_query.iter_mut()
+    {
+        let (PlayerState::Sliding, Some(normal)) = (player_state, slope.0) else {
+            continue;
+        };
+
+        let downhill = (Vec3::NEG_Y + normal * normal.y).normalize_or_zero();
+        controller.translation =
+            Some(downhill * SLIDING_SPEED * time.delta_seconds() + ground_motion.displacement);
+
+        let facing = Vec3::new(downhill.x, 0.0, downhill.z);
+        if facing != Vec3::ZERO {
+            transform.look_to(-facing, Vec3::Y);
+        }
+    }
+}
+
+fn emit_footsteps(
+    mut footsteps_query: Query<(&mut Footsteps, &PlayerState, &Transform)>,
+    mut noise_events: EventWriter<Noise>,
+    time: Res<Time>,
+) {
+    for (mut footsteps, player_state, transform) in footsteps_query.iter_mut() {
+        let loudness = match player_state {
+            PlayerState::Walking => WALKING_LOUDNESS,
+            PlayerState::Runing => RUNNING_LOUDNESS,
+            PlayerState::CrouchWalking => CROUCHING_LOUDNESS,
+            _ => continue,
+        };
+
+        if footsteps.0.tick(time.delta()).just_finished() {
+            noise_events.send(Noise {
+                position: transform.translation,
+                loudness,
+            });
         }
     }
 }
 
 const GRAVITY: f32 = 9.8;
+/// Downward speed of grounded characters, keeping them pressed onto the
+/// ground. Every fall starts at this speed.
+const GROUND_SNAP_SPEED: f32 = 4.5;
+const HARD_LANDING_SPEED: f32 = 7.0;
+const LANDING_TRAUMA_PER_SPEED: f32 = 0.1;
+/// Reached after falling about 0.8 m, stepping down stairs or small ledges
+/// stays quiet.
+const LANDING_NOISE_SPEED: f32 = 6.0;
+const LANDING_LOUDNESS_PER_SPEED: f32 = 0.1;
+
+/// Local players landing from a fall make noise, and shake the camera when
+/// landing hard. Only the frame a character touches down counts, standing
+/// keeps the speed at the ground snap value.
+fn emit_landings(
+    mut controller_query: Query<(
+        &KinematicCharacterControllerOutput,
+        &VerticalSpeed,
+        &mut Grounded,
+        &Transform,
+        &LocalPlayer,
+    )>,
+    mut trauma_events: EventWriter<AddTrauma>,
+    mut noise_events: EventWriter<Noise>,
+) {
+    for (controller, vertical_speed, mut grounded, transform, local_player) in
+        controller_query.iter_mut()
+    {
+        let landed = controller.grounded && !grounded.0;
+        grounded.0 = controller.grounded;
+
+        if !landed {
+            continue;
+        }
+
+        let landing_speed = -vertical_speed.0;
+        if landing_speed > LANDING_NOISE_SPEED {
+            noise_events.send(Noise {
+                position: transform.translation,
+                loudness: landing_speed * LANDING_LOUDNESS_PER_SPEED,
+            });
+        }
+
+        if landing_speed > HARD_LANDING_SPEED {
+            trauma_events.send(AddTrauma {
+                trauma: (landing_speed - HARD_LANDING_SPEED) * LANDING_TRAUMA_PER_SPEED,
+                player: Some(*local_player),
+            });
+        }
+    }
+}
+
 fn apply_gravity(
-    mut controller_query: Query<(&KinematicCharacterControllerOutput, &mut VerticalSpeed)>,
+    mut controller_query: Query<(
+        &KinematicCharacterControllerOutput,
+        &mut VerticalSpeed,
+        &PlayerState,
+    )>,
     time: Res<Time>,
 ) {
-    for (controller, mut vertical_speed) in controller_query.iter_mut() {
+    for (controller, mut vertical_speed, player_state) in controller_query.iter_mut() {
+        // Buoyancy replaces gravity in water, see `swim`.
+        if *player_state == PlayerState::Swimming {
+            continue;
+        }
+
+        // Sliding follows the slope instead of falling, and doesn't end in a
+        // hard landing at the bottom. Hanging and ladder climbing characters
+        // hold on.
+        if matches!(
+            player_state,
+            PlayerState::Sliding
+                | PlayerState::Hanging
+                | PlayerState::Climbing
+                | PlayerState::LadderClimbing
+        ) {
+            vertical_speed.0 = 0.0;
+            continue;
+        }
+
         match controller.grounded {
-            true => vertical_speed.0 = -4.5,
+            true => {
+                vertical_speed.0 = -GROUND_SNAP_SPEED;
+            }
             false => vertical_speed.0 -= GRAVITY * time.delta_seconds(),
         }
     }
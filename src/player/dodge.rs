@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use super::controller::{CharacterInput, GroundMotion, PlayerState, VerticalSpeed};
+use crate::health::Health;
+
+/// Speed of a dodge over its duration, sampled linearly between keys. A quick
+/// burst that eases out so the character doesn't stop dead.
+const DODGE_CURVE: [(f32, f32); 4] = [(0.0, 3.0), (0.08, 9.0), (0.25, 6.0), (0.45, 0.0)];
+const DODGE_COOLDOWN: f32 = 0.6;
+const INVULNERABLE_FROM: f32 = 0.05;
+const INVULNERABLE_UNTIL: f32 = 0.3;
+
+fn dodge_duration() -> f32 {
+    DODGE_CURVE[DODGE_CURVE.len() - 1].0
+}
+
+fn dodge_speed(elapsed: f32) -> f32 {
+    DODGE_CURVE
+        .windows(2)
+        .find(|keys| elapsed <= keys[1].0)
+        .map(|keys| {
+            let ((from_time, from_speed), (to_time, to_speed)) = (keys[0], keys[1]);
+            let blend = (elapsed - from_time) / (to_time - from_time);
+
+            from_speed + (to_speed - from_speed) * blend.clamp(0.0, 1.0)
+        })
+        .unwrap_or(0.0)
+}
+
+/// A burst of movement in a direction fixed when it starts, steering is
+/// ignored until it is over.
+#[derive(Component, Default)]
+pub struct Dodge {
+    direction: Vec3,
+    elapsed: Option<f32>,
+    ready_at: f32,
+}
+
+impl Dodge {
+    pub fn active(&self) -> bool {
+        self.elapsed.is_some()
+    }
+}
+
+/// Starts a dodge towards the movement input, or a backstep without any,
+/// when the character is grounded and the cooldown passed.
+pub(super) fn start_dodges(
+    mut character_query: Query<(
+        &CharacterInput,
+        &Transform,
+        &KinematicCharacterControllerOutput,
+        &mut PlayerState,
+        &mut Dodge,
+    )>,
+    time: Res<Time>,
+) {
+    for (input, transform, output, mut player_state, mut dodge) in character_query.iter_mut() {
+        if !input.dodge
+            || !output.grounded
+            || dodge.active()
+            || matches!(
+                *player_state,
+                PlayerState::Sliding | PlayerState::Swimming | PlayerState::LadderClimbing
+            )
+            || time.elapsed_seconds() < dodge.ready_at
+        {
+            continue;
+        }
+
+        let backstep = -Vec3::new(transform.back().x, 0.0, transform.back().z);
+        dodge.direction = input
+            .movement
+            .try_normalize()
+            .unwrap_or(backstep.normalize_or_zero());
+        dodge.elapsed = Some(0.0);
+        *player_state = PlayerState::Dodging;
+    }
+}
+
+/// Moves dodging characters through the `KinematicCharacterController` so
+/// walls still stop them, and keeps them invulnerable for the i-frames.
+pub(super) fn perform_dodges(
+    mut character_query: Query<(
+        Entity,
+        &mut KinematicCharacterController,
+        &mut Transform,
+        &VerticalSpeed,
+        &GroundMotion,
+        &mut PlayerState,
+        &mut Dodge,
+    )>,
+    mut health_query: Query<&mut Health>,
+    time: Res<Time>,
+) {
+    for (
+        entity,
+        mut controller,
+        mut transform,
+        vertical_speed,
+        ground_motion,
+        mut player_state,
+        mut dodge,
+    ) in character_query.iter_mut()
+    {
+        let Some(elapsed) = dodge.elapsed else {
+            continue;
+        };
+
+        let delta_seconds = time.delta_seconds();
+        let movement = dodge.direction * dodge_speed(elapsed) * delta_seconds
+            + Vec3::Y * vertical_speed.0 * delta_seconds
+            + ground_motion.displacement;
+        controller.translation = Some(movement);
+
+        // Rolling backwards keeps facing forward.
+        let backstep = dodge.direction.dot(*transform.back()) < 0.0;
+        if !backstep {
+            transform.look_to(-dodge.direction, Vec3::Y);
+        }
+
+        let elapsed = elapsed + delta_seconds;
+        let finished = elapsed >= dodge_duration();
+        dodge.elapsed = (!finished).then_some(elapsed);
+        if finished {
+            dodge.ready_at = time.elapsed_seconds() + DODGE_COOLDOWN;
+            // Hand the state back, it is picked again from the input next
+            // frame.
+            *player_state = PlayerState::Idle;
+        }
+
+        if let Ok(mut health) = health_query.get_mut(entity) {
+            health.set_invulnerable(
+                !finished && (INVULNERABLE_FROM..INVULNERABLE_UNTIL).contains(&elapsed),
+            );
+        }
+    }
+}
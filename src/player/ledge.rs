@@ -0,0 +1,256 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use super::{
+    climbing::LadderClimber,
+    controller::{character_capsule, CharacterInput, Crouched, VerticalSpeed},
+};
+
+const PROBE_RADIUS: f32 = 0.15;
+const WALL_PROBE_HEIGHT: f32 = 1.3;
+const WALL_PROBE_DISTANCE: f32 = 0.6;
+const MIN_LEDGE_HEIGHT: f32 = 1.0;
+const MAX_LEDGE_HEIGHT: f32 = 2.2;
+const LEDGE_DEPTH: f32 = 0.2;
+const MAX_WALL_SLANT: f32 = 0.3;
+/// Height of the hands above the feet while hanging.
+const HANG_HEIGHT: f32 = 1.4;
+const HANG_DISTANCE: f32 = 0.35;
+const SHIMMY_SPEED: f32 = 0.8;
+const MAX_SHIMMY_STEP: f32 = 0.2;
+const CLIMB_DURATION: f32 = 0.8;
+const CLIMB_DEPTH: f32 = 0.5;
+const REGRAB_DELAY: f32 = 0.5;
+
+#[derive(Clone, Copy)]
+struct Ledge {
+    /// Point on the top edge the hands hold on to.
+    point: Vec3,
+    /// Horizontal normal of the wall below the edge.
+    normal: Vec3,
+}
+
+impl Ledge {
+    fn hang_position(&self) -> Vec3 {
+        self.point - Vec3::Y * HANG_HEIGHT + self.normal * HANG_DISTANCE
+    }
+
+    fn top_position(&self) -> Vec3 {
+        self.point - self.normal * CLIMB_DEPTH
+    }
+}
+
+struct Climb {
+    from: Vec3,
+    to: Vec3,
+    elapsed: f32,
+}
+
+/// The ledge an airborne character caught, and its progress climbing onto
+/// it.
+#[derive(Component, Default)]
+pub struct LedgeGrab {
+    ledge: Option<Ledge>,
+    climb: Option<Climb>,
+    regrab_at: f32,
+}
+
+impl LedgeGrab {
+    pub fn hanging(&self) -> bool {
+        self.ledge.is_some()
+    }
+
+    pub fn climbing(&self) -> bool {
+        self.climb.is_some()
+    }
+}
+
+/// Looks for a wall in front of the character with a top edge within reach,
+/// a forward shape-cast finds the wall and a downward one its top.
+fn find_ledge(
+    rapier_context: &RapierContext,
+    entity: Entity,
+    feet: Vec3,
+    facing: Vec3,
+) -> Option<Ledge> {
+    let ball = Collider::ball(PROBE_RADIUS);
+    let filter = QueryFilter::default()
+        .exclude_collider(entity)
+        .exclude_sensors();
+
+    let origin = feet + Vec3::Y * WALL_PROBE_HEIGHT;
+    rapier_context.cast_shape(
+        origin,
+        Quat::IDENTITY,
+        facing,
+        &ball,
+        WALL_PROBE_DISTANCE,
+        true,
+        filter,
+    )?;
+
+    let (_, wall) = rapier_context.cast_ray_and_get_normal(
+        origin,
+        facing,
+        WALL_PROBE_DISTANCE + PROBE_RADIUS,
+        true,
+        filter,
+    )?;
+
+    if wall.normal.y.abs() > MAX_WALL_SLANT {
+        return None;
+    }
+
+    let normal = Vec3::new(wall.normal.x, 0.0, wall.normal.z).normalize_or_zero();
+    let top = Vec3::new(
+        wall.point.x,
+        feet.y + MAX_LEDGE_HEIGHT + PROBE_RADIUS,
+        wall.point.z,
+    ) - normal * LEDGE_DEPTH;
+
+    let (_, toi) = rapier_context.cast_shape(
+        top,
+        Quat::IDENTITY,
+        Vec3::NEG_Y,
+        &ball,
+        MAX_LEDGE_HEIGHT - MIN_LEDGE_HEIGHT,
+        false,
+        filter,
+    )?;
+
+    let height = top.y - toi.toi - PROBE_RADIUS;
+
+    Some(Ledge {
+        point: Vec3::new(wall.point.x, height, wall.point.z),
+        normal,
+    })
+}
+
+/// Catches ledges in front of airborne characters and snaps them to a
+/// hanging position below the edge.
+pub(super) fn grab_ledges(
+    mut character_query: Query<(
+        Entity,
+        &mut Transform,
+        &KinematicCharacterControllerOutput,
+        &Crouched,
+        &LadderClimber,
+        &mut LedgeGrab,
+    )>,
+    rapier_context: Res<RapierContext>,
+    time: Res<Time>,
+) {
+    for (entity, mut transform, output, crouched, climber, mut ledge_grab) in
+        character_query.iter_mut()
+    {
+        if output.grounded
+            || crouched.0
+            || climber.attached()
+            || ledge_grab.hanging()
+            || time.elapsed_seconds() < ledge_grab.regrab_at
+        {
+            continue;
+        }
+
+        let facing = Vec3::new(transform.back().x, 0.0, transform.back().z).normalize_or_zero();
+        let Some(ledge) = find_ledge(&rapier_context, entity, transform.translation, facing) else {
+            continue;
+        };
+
+        transform.translation = ledge.hang_position();
+        transform.look_to(ledge.normal, Vec3::Y);
+        ledge_grab.ledge = Some(ledge);
+    }
+}
+
+/// Moves hanging characters along the edge, lets them drop with crouch, or
+/// climb up with jump when there is room on top.
+pub(super) fn hang_from_ledges(
+    mut character_query: Query<(
+        Entity,
+        &mut Transform,
+        &CharacterInput,
+        &mut VerticalSpeed,
+        &mut LedgeGrab,
+    )>,
+    rapier_context: Res<RapierContext>,
+    time: Res<Time>,
+) {
+    for (entity, mut transform, input, mut vertical_speed, mut ledge_grab) in
+        character_query.iter_mut()
+    {
+        let Some(ledge) = ledge_grab.ledge else {
+            continue;
+        };
+
+        vertical_speed.0 = 0.0;
+
+        if let Some(climb) = &mut ledge_grab.climb {
+            climb.elapsed += time.delta_seconds();
+            let progress = (climb.elapsed / CLIMB_DURATION).min(1.0);
+
+            // Pull up first, then step forward onto the top.
+            let rise = (progress / 0.6).min(1.0);
+            let step = ((progress - 0.6) / 0.4).max(0.0);
+            transform.translation = Vec3::new(
+                climb.from.x + (climb.to.x - climb.from.x) * step,
+                climb.from.y + (climb.to.y - climb.from.y) * rise,
+                climb.from.z + (climb.to.z - climb.from.z) * step,
+            );
+
+            if progress >= 1.0 {
+                *ledge_grab = LedgeGrab::default();
+            }
+
+            continue;
+        }
+
+        if input.crouch {
+            ledge_grab.ledge = None;
+            ledge_grab.regrab_at = time.elapsed_seconds() + REGRAB_DELAY;
+            continue;
+        }
+
+        let filter = QueryFilter::default()
+            .exclude_collider(entity)
+            .exclude_sensors();
+
+        if input.jump {
+            let blocked = rapier_context
+                .intersection_with_shape(
+                    ledge.top_position() + Vec3::Y * 0.05,
+                    Quat::IDENTITY,
+                    &character_capsule(false),
+                    filter,
+                )
+                .is_some();
+
+            if !blocked {
+                ledge_grab.climb = Some(Climb {
+                    from: transform.translation,
+                    to: ledge.top_position(),
+                    elapsed: 0.0,
+                });
+                continue;
+            }
+        }
+
+        let along = ledge.normal.cross(Vec3::Y);
+        let shimmy = input.movement.dot(along) * SHIMMY_SPEED * time.delta_seconds();
+        if shimmy == 0.0 {
+            continue;
+        }
+
+        // Only move as far as the edge goes on, re-detecting it from the new
+        // position also follows edges that bend.
+        let moved = ledge.hang_position() + along * shimmy;
+        let next = find_ledge(&rapier_context, entity, moved, -ledge.normal)
+            .filter(|next| (next.point.y - ledge.point.y).abs() < MAX_SHIMMY_STEP);
+
+        if let Some(next) = next {
+            transform.translation = next.hang_position();
+            transform.look_to(next.normal, Vec3::Y);
+            ledge_grab.ledge = Some(next);
+        }
+    }
+}
@@ -3,54 +3,234 @@ use bevy_rapier3d::prelude::*;
 use bevy_scene_hook::{HookPlugin, HookedSceneBundle, SceneHook};
 
 mod animation;
+mod climbing;
 mod controller;
+mod dodge;
+mod ledge;
+mod movement_profile;
+mod shooting;
+mod swimming;
 
-use crate::camera_controller::CameraTarget;
-use animation::PlayerAnimationPlugin;
+use crate::{
+    aim::AimTarget,
+    camera_controller::CameraTarget,
+    health::{Died, Health},
+    interaction::InteractionFocus,
+    local_player::{InputDevice, LocalPlayer, LocalPlayers, PlayerInput},
+    lock_on::LockOnTarget,
+};
+use animation::{AnimationOwner, AnimationSet, PlayerAnimationPlugin};
+use climbing::LadderClimber;
 use controller::{
-    MovementDirection, PlayerControllerBundle, PlayerControllerPlugin, PlayerState, VerticalSpeed,
+    character_capsule, Crouched, Footsteps, Grounded, MovementDirection, PlayerControllerBundle,
+    PlayerControllerPlugin, Slope, VerticalSpeed,
 };
+use dodge::Dodge;
+use ledge::LedgeGrab;
+use movement_profile::{MovementProfile, MovementProfilePlugin};
+use shooting::{PlayerShootingPlugin, Weapon, WeaponDescriptor};
+
+pub use controller::{CharacterInput, GroundMotion, PlayerState};
+pub use swimming::Swimmer;
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(HookPlugin)
-            .add_plugins((PlayerControllerPlugin, PlayerAnimationPlugin))
-            .add_systems(Startup, spawn_player);
+            .add_plugins((
+                PlayerControllerPlugin,
+                MovementProfilePlugin,
+                PlayerAnimationPlugin,
+                PlayerShootingPlugin,
+            ))
+            .add_systems(Startup, spawn_players)
+            .add_systems(Update, (respawn_players, follow_stance_height));
     }
 }
 
-fn spawn_player(mut commands: Commands, assets: Res<AssetServer>) {
-    let player = (
-        HookedSceneBundle {
-            scene: SceneBundle {
-                scene: assets.load("player.gltf#Scene0"),
-                ..default()
-            },
-            hook: SceneHook::new(|entity, commands| {
-                if entity.get::<Handle<Mesh>>().is_some() {
-                    commands.insert(NoFrustumCulling);
-                }
-            }),
-        },
+const PLAYER_SPACING: f32 = 1.2;
+const CAMERA_TARGET_HEIGHT: f32 = 1.0;
+const CROUCHED_CAMERA_TARGET_HEIGHT: f32 = 0.6;
+const CLIMBING_CAMERA_TARGET_HEIGHT: f32 = 1.6;
+const CAMERA_TARGET_SPEED: f32 = 8.0;
+
+const PLAYER_ANIMATIONS: [(PlayerState, usize); 14] = [
+    (PlayerState::Idle, 0),
+    (PlayerState::Jumping, 1),
+    (PlayerState::Runing, 2),
+    (PlayerState::Aiming, 3),
+    (PlayerState::Sprinting, 4),
+    (PlayerState::Walking, 5),
+    (PlayerState::Sliding, 1),
+    (PlayerState::Crouching, 0),
+    (PlayerState::CrouchWalking, 5),
+    (PlayerState::Hanging, 1),
+    (PlayerState::Climbing, 1),
+    (PlayerState::Dodging, 2),
+    (PlayerState::Swimming, 5),
+    (PlayerState::LadderClimbing, 5),
+];
+
+fn spawn_players(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    local_players: Res<LocalPlayers>,
+) {
+    for index in 0..local_players.count {
+        let local_player = LocalPlayer(index);
+
+        spawn_player(
+            &mut commands,
+            &assets,
+            local_player,
+            local_players.input_device(local_player),
+        );
+    }
+}
+
+fn spawn_point(local_player: LocalPlayer) -> Vec3 {
+    Vec3::new(local_player.0 as f32 * PLAYER_SPACING, 0.0, 0.0)
+}
+
+fn spawn_player(
+    commands: &mut Commands,
+    assets: &AssetServer,
+    local_player: LocalPlayer,
+    input_device: InputDevice,
+) {
+    let player = commands.spawn_empty().id();
+
+    let bundle = (
+        character_bundle(
+            assets,
+            Transform::from_translation(spawn_point(local_player)),
+            player,
+        ),
+        Weapon::new(WeaponDescriptor {
+            fire_rate: 8.0,
+            magazine_size: 30,
+            reload_time: 1.5,
+            range: 100.0,
+            damage: 25.0,
+            recoil: 0.02,
+            trauma: 0.15,
+        }),
+        Health::new(100.0),
+        Footsteps::new(0.4),
+        local_player,
+        input_device,
+        PlayerInput::default(),
+        LockOnTarget::default(),
+        InteractionFocus::default(),
+        AimTarget::default(),
+        Name::new(format!("Player {}", local_player.0 + 1)),
+    );
+
+    commands
+        .entity(player)
+        .insert(bundle)
+        .with_children(|parent| {
+            parent.spawn((
+                CameraTarget { priority: 0 },
+                local_player,
+                TransformBundle {
+                    local: Transform::from_xyz(0.0, CAMERA_TARGET_HEIGHT, 0.0),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn respawn_players(
+    mut died_events: EventReader<Died>,
+    mut player_query: Query<(&LocalPlayer, &mut Transform, &mut Health)>,
+) {
+    for died in died_events.read() {
+        let Ok((local_player, mut transform, mut health)) = player_query.get_mut(died.entity)
+        else {
+            continue;
+        };
+
+        transform.translation = spawn_point(*local_player);
+        health.restore();
+    }
+}
+
+/// Eases the camera target of a player down while crouched, up while hanging
+/// from a ledge or climbing a ladder so the camera looks where they climb,
+/// and back after standing.
+fn follow_stance_height(
+    mut target_query: Query<(&Parent, &mut Transform), With<CameraTarget>>,
+    stance_query: Query<(&PlayerState, &Crouched)>,
+    time: Res<Time>,
+) {
+    for (parent, mut transform) in target_query.iter_mut() {
+        let Ok((player_state, crouched)) = stance_query.get(parent.get()) else {
+            continue;
+        };
+
+        let height = match (player_state, crouched.0) {
+            (PlayerState::Hanging | PlayerState::Climbing | PlayerState::LadderClimbing, _) => {
+                CLIMBING_CAMERA_TARGET_HEIGHT
+            }
+            (_, true) => CROUCHED_CAMERA_TARGET_HEIGHT,
+            (_, false) => CAMERA_TARGET_HEIGHT,
+        };
+
+        let blend = (CAMERA_TARGET_SPEED * time.delta_seconds()).min(1.0);
+        transform.translation.y += (height - transform.translation.y) * blend;
+    }
+}
+
+/// Everything a character needs to be driven by the player controller, the
+/// caller adds an input source on top of it.
+pub fn character_bundle(assets: &AssetServer, transform: Transform, owner: Entity) -> impl Bundle {
+    (
+        character_scene(assets, "player.gltf#Scene0", transform, owner),
+        AnimationSet::load(assets, "player.gltf", &PLAYER_ANIMATIONS),
+        assets.load::<MovementProfile>("characters/default.movement.ron"),
         PlayerControllerBundle {
             initial_state: PlayerState::Idle,
-            movement_direction: MovementDirection(Vec3::new(0.0, 0.0, -1.0)),
+            movement_direction: MovementDirection(Vec3::ZERO),
             initial_vertical_speed: VerticalSpeed(0.0),
-            collider: Collider::capsule(Vec3::new(0.0, 0.3, 0.0), Vec3::new(0.0, 1.5, 0.0), 0.3),
+            grounded: Grounded::default(),
+            character_input: CharacterInput::default(),
+            ground_motion: GroundMotion::default(),
+            slope: Slope(None),
+            crouched: Crouched::default(),
+            ledge_grab: LedgeGrab::default(),
+            dodge: Dodge::default(),
+            swimmer: Swimmer::default(),
+            ladder_climber: LadderClimber::default(),
+            collider: character_capsule(false),
             kinematic_character_controller: KinematicCharacterController { ..default() },
         },
-        Name::new("Player"),
-    );
+    )
+}
 
-    commands.spawn(player).with_children(|parent| {
-        parent.spawn((
-            CameraTarget,
-            TransformBundle {
-                local: Transform::from_xyz(0.0, 1.0, 0.0),
-                ..default()
-            },
-        ));
-    });
+/// Spawns the glTF scene of a character rooted at `owner`, binding every
+/// animation player inside it back to the character.
+fn character_scene(
+    assets: &AssetServer,
+    scene: &str,
+    transform: Transform,
+    owner: Entity,
+) -> HookedSceneBundle {
+    HookedSceneBundle {
+        scene: SceneBundle {
+            scene: assets.load(scene.to_owned()),
+            transform,
+            ..default()
+        },
+        hook: SceneHook::new(move |entity, commands| {
+            if entity.get::<Handle<Mesh>>().is_some() {
+                commands.insert(NoFrustumCulling);
+            }
+
+            if entity.contains::<AnimationPlayer>() {
+                commands.insert(AnimationOwner(owner));
+            }
+        }),
+    }
 }
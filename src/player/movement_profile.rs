@@ -0,0 +1,115 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::{BoxedFuture, HashSet},
+};
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+use thiserror::Error;
+
+#[derive(Deserialize)]
+pub struct AutostepProfile {
+    pub max_height: f32,
+    pub min_width: f32,
+}
+
+/// How a character's controller handles steps and slopes, loaded from a
+/// `.movement.ron` file. Angles are in degrees and lengths in meters.
+#[derive(Asset, TypePath, Deserialize)]
+pub struct MovementProfile {
+    /// Steeper slopes can't be walked up and make the character slide down.
+    pub max_slope_climb_angle: f32,
+    pub min_slope_slide_angle: f32,
+    pub autostep: Option<AutostepProfile>,
+    pub snap_to_ground: Option<f32>,
+    /// Gap kept between the character and what it collides with.
+    pub offset: f32,
+}
+
+impl MovementProfile {
+    fn apply(&self, controller: &mut KinematicCharacterController) {
+        controller.max_slope_climb_angle = self.max_slope_climb_angle.to_radians();
+        controller.min_slope_slide_angle = self.min_slope_slide_angle.to_radians();
+        controller.autostep = self.autostep.as_ref().map(|autostep| CharacterAutostep {
+            max_height: CharacterLength::Absolute(autostep.max_height),
+            min_width: CharacterLength::Absolute(autostep.min_width),
+            include_dynamic_bodies: true,
+        });
+        controller.snap_to_ground = self.snap_to_ground.map(CharacterLength::Absolute);
+        controller.offset = CharacterLength::Absolute(self.offset);
+    }
+}
+
+pub struct MovementProfilePlugin;
+
+impl Plugin for MovementProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<MovementProfile>()
+            .init_asset_loader::<MovementProfileLoader>()
+            .add_systems(Update, apply_movement_profiles);
+    }
+}
+
+/// Configures controllers once their profile loaded, again whenever the file
+/// changes, and right away for characters spawned later on.
+fn apply_movement_profiles(
+    mut controller_query: Query<(
+        Ref<Handle<MovementProfile>>,
+        &mut KinematicCharacterController,
+    )>,
+    mut profile_events: EventReader<AssetEvent<MovementProfile>>,
+    profiles: Res<Assets<MovementProfile>>,
+) {
+    let changed = profile_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
+
+    for (handle, mut controller) in controller_query.iter_mut() {
+        if !handle.is_changed() && !changed.contains(&handle.id()) {
+            continue;
+        }
+
+        if let Some(profile) = profiles.get(handle.id()) {
+            profile.apply(&mut controller);
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct MovementProfileLoader;
+
+#[derive(Debug, Error)]
+pub enum MovementProfileLoaderError {
+    #[error("could not read the movement profile: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse the movement profile: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for MovementProfileLoader {
+    type Asset = MovementProfile;
+    type Settings = ();
+    type Error = MovementProfileLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<MovementProfile, MovementProfileLoaderError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            Ok(ron::de::from_bytes::<MovementProfile>(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["movement.ron"]
+    }
+}
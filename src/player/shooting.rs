@@ -0,0 +1,217 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use std::time::Duration;
+
+use super::PlayerState;
+use crate::{
+    camera_controller::CameraController,
+    camera_shake::AddTrauma,
+    health::Damage,
+    local_player::{LocalPlayer, PlayerAction, PlayerInput},
+    perception::Noise,
+    schedule::InGameSet,
+};
+
+pub struct WeaponDescriptor {
+    /// Shots per second.
+    pub fire_rate: f32,
+    pub magazine_size: u32,
+    /// Seconds a reload takes, the weapon can't fire in the meantime.
+    pub reload_time: f32,
+    pub range: f32,
+    pub damage: f32,
+    pub recoil: f32,
+    pub trauma: f32,
+}
+
+#[derive(Component)]
+pub struct Weapon {
+    pub cooldown: Timer,
+    pub reload: Timer,
+    pub ammo: u32,
+    pub magazine_size: u32,
+    pub range: f32,
+    pub damage: f32,
+    pub recoil: f32,
+    pub trauma: f32,
+}
+
+impl Weapon {
+    pub fn new(descriptor: WeaponDescriptor) -> Weapon {
+        let mut cooldown = Timer::from_seconds(1.0 / descriptor.fire_rate, TimerMode::Once);
+        cooldown.set_elapsed(cooldown.duration());
+
+        let mut reload = Timer::from_seconds(descriptor.reload_time, TimerMode::Once);
+        reload.set_elapsed(reload.duration());
+
+        Weapon {
+            cooldown,
+            reload,
+            ammo: descriptor.magazine_size,
+            magazine_size: descriptor.magazine_size,
+            range: descriptor.range,
+            damage: descriptor.damage,
+            recoil: descriptor.recoil,
+            trauma: descriptor.trauma,
+        }
+    }
+
+    pub fn reloading(&self) -> bool {
+        !self.reload.finished()
+    }
+}
+
+#[derive(Event)]
+pub struct ShotHit {
+    pub point: Vec3,
+    pub normal: Vec3,
+}
+
+#[derive(Component)]
+struct Impact(Timer);
+
+pub struct PlayerShootingPlugin;
+
+impl Plugin for PlayerShootingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ShotHit>()
+            .add_systems(
+                Update,
+                (reload_weapon, fire_weapon)
+                    .chain()
+                    .in_set(InGameSet::UserInput),
+            )
+            .add_systems(
+                Update,
+                (spawn_impacts, despawn_impacts).in_set(InGameSet::EntityUpdates),
+            );
+    }
+}
+
+/// The magazine is only refilled once the reload timer runs out.
+fn reload_weapon(mut weapon_query: Query<(&mut Weapon, &PlayerInput)>, time: Res<Time>) {
+    for (mut weapon, player_input) in weapon_query.iter_mut() {
+        if weapon.reload.tick(time.delta()).just_finished() {
+            weapon.ammo = weapon.magazine_size;
+        }
+
+        if player_input.actions.just_pressed(PlayerAction::Reload)
+            && !weapon.reloading()
+            && weapon.ammo < weapon.magazine_size
+        {
+            weapon.reload.reset();
+        }
+    }
+}
+
+const SHOT_LOUDNESS: f32 = 1.5;
+
+#[allow(clippy::too_many_arguments)]
+fn fire_weapon(
+    mut shooter_query: Query<(
+        Entity,
+        &PlayerState,
+        &mut Weapon,
+        &PlayerInput,
+        &LocalPlayer,
+        &Transform,
+    )>,
+    mut camera_query: Query<(&LocalPlayer, &Transform, &mut CameraController)>,
+    mut shot_hit_events: EventWriter<ShotHit>,
+    mut damage_events: EventWriter<Damage>,
+    mut trauma_events: EventWriter<AddTrauma>,
+    mut noise_events: EventWriter<Noise>,
+    rapier_context: Res<RapierContext>,
+    time: Res<Time>,
+) {
+    for (shooter, player_state, mut weapon, player_input, local_player, transform) in
+        shooter_query.iter_mut()
+    {
+        weapon.cooldown.tick(time.delta());
+
+        if *player_state != PlayerState::Aiming
+            || !player_input.actions.pressed(PlayerAction::Fire)
+            || !weapon.cooldown.finished()
+            || weapon.reloading()
+            || weapon.ammo == 0
+        {
+            continue;
+        }
+
+        let Some((_, camera_transform, mut camera_controller)) = camera_query
+            .iter_mut()
+            .find(|(owner, _, _)| *owner == local_player)
+        else {
+            continue;
+        };
+
+        weapon.cooldown.reset();
+        weapon.ammo -= 1;
+        camera_controller.add_recoil(weapon.recoil);
+        trauma_events.send(AddTrauma {
+            trauma: weapon.trauma,
+            player: Some(*local_player),
+        });
+        noise_events.send(Noise {
+            position: transform.translation,
+            loudness: SHOT_LOUDNESS,
+        });
+
+        let hit = rapier_context.cast_ray_and_get_normal(
+            camera_transform.translation,
+            *camera_transform.forward(),
+            weapon.range,
+            true,
+            QueryFilter::default()
+                .exclude_collider(shooter)
+                .exclude_sensors(),
+        );
+
+        if let Some((entity, intersection)) = hit {
+            damage_events.send(Damage {
+                entity,
+                amount: weapon.damage,
+            });
+            shot_hit_events.send(ShotHit {
+                point: intersection.point,
+                normal: intersection.normal,
+            });
+        }
+    }
+}
+
+fn spawn_impacts(
+    mut commands: Commands,
+    mut shot_hit_events: EventReader<ShotHit>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for hit in shot_hit_events.read() {
+        let impact = (
+            PbrBundle {
+                mesh: meshes.add(Cylinder::new(0.05, 0.01).mesh()),
+                material: materials.add(Color::BLACK),
+                transform: Transform::from_translation(hit.point + hit.normal * 0.005)
+                    .with_rotation(Quat::from_rotation_arc(Vec3::Y, hit.normal)),
+                ..default()
+            },
+            Impact(Timer::new(Duration::from_secs(5), TimerMode::Once)),
+            Name::new("Impact"),
+        );
+
+        commands.spawn(impact);
+    }
+}
+
+fn despawn_impacts(
+    mut commands: Commands,
+    mut impact_query: Query<(Entity, &mut Impact)>,
+    time: Res<Time>,
+) {
+    for (entity, mut impact) in impact_query.iter_mut() {
+        if impact.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
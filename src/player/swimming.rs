@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use super::{
+    climbing::LadderClimber,
+    controller::{CharacterInput, PlayerState, VerticalSpeed},
+    ledge::LedgeGrab,
+};
+use crate::{health::Damage, water::Water};
+
+/// Depth of the feet below the surface at which a character starts swimming,
+/// and below which it wades out again.
+const SWIM_DEPTH: f32 = 1.1;
+const WADE_DEPTH: f32 = 0.9;
+/// Depth of the feet while treading water with the head above the surface.
+const TREAD_DEPTH: f32 = 1.3;
+const HEAD_DEPTH: f32 = 1.6;
+const SWIM_SPEED: f32 = 1.6;
+const DIVE_SPEED: f32 = 1.2;
+const FLOAT_SPEED: f32 = 0.6;
+const BUOYANCY: f32 = 3.0;
+const WATER_DRAG: f32 = 4.0;
+const BREATH_SECONDS: f32 = 10.0;
+const BREATH_RECOVERY: f32 = 2.0;
+const DROWNING_DAMAGE: f32 = 10.0;
+
+/// Tracks whether a character is swimming, and how long it can still hold
+/// its breath.
+#[derive(Component)]
+pub struct Swimmer {
+    depth: Option<f32>,
+    swimming: bool,
+    breath: f32,
+}
+
+impl Default for Swimmer {
+    fn default() -> Swimmer {
+        Swimmer {
+            depth: None,
+            swimming: false,
+            breath: BREATH_SECONDS,
+        }
+    }
+}
+
+impl Swimmer {
+    pub fn breath_fraction(&self) -> f32 {
+        self.breath / BREATH_SECONDS
+    }
+}
+
+/// Measures how deep characters are in water and switches them to swimming
+/// once submerged far enough. Hanging from a ledge or holding on to a
+/// ladder, like when climbing out, takes precedence.
+pub(super) fn update_swimming(
+    mut character_query: Query<(
+        &Transform,
+        &LedgeGrab,
+        &LadderClimber,
+        &mut PlayerState,
+        &mut Swimmer,
+    )>,
+    water_query: Query<(&Water, &GlobalTransform)>,
+) {
+    for (transform, ledge_grab, climber, mut player_state, mut swimmer) in
+        character_query.iter_mut()
+    {
+        swimmer.depth = water_query
+            .iter()
+            .filter_map(|(water, water_transform)| {
+                water.depth(water_transform, transform.translation)
+            })
+            .reduce(f32::max);
+
+        let swimming = !ledge_grab.hanging()
+            && !climber.attached()
+            && swimmer.depth.is_some_and(|depth| match swimmer.swimming {
+                true => depth > WADE_DEPTH,
+                false => depth > SWIM_DEPTH,
+            });
+
+        if swimming {
+            *player_state = PlayerState::Swimming;
+        } else if swimmer.swimming && *player_state == PlayerState::Swimming {
+            // Hand the state back, it is picked again from the input next
+            // frame.
+            *player_state = PlayerState::Idle;
+        }
+
+        swimmer.swimming = swimming;
+    }
+}
+
+/// Moves swimmers in 3D, buoyancy floats them up to treading depth unless
+/// they dive, and they drown slowly once out of breath.
+pub(super) fn swim(
+    mut character_query: Query<(
+        Entity,
+        &mut KinematicCharacterController,
+        &mut Transform,
+        &CharacterInput,
+        &mut VerticalSpeed,
+        &mut Swimmer,
+    )>,
+    mut damage_events: EventWriter<Damage>,
+    time: Res<Time>,
+) {
+    let delta_seconds = time.delta_seconds();
+
+    for (entity, mut controller, mut transform, input, mut vertical_speed, mut swimmer) in
+        character_query.iter_mut()
+    {
+        let depth = swimmer.depth.unwrap_or(0.0);
+
+        match depth > HEAD_DEPTH {
+            true => swimmer.breath = (swimmer.breath - delta_seconds).max(0.0),
+            false => {
+                swimmer.breath =
+                    (swimmer.breath + BREATH_RECOVERY * delta_seconds).min(BREATH_SECONDS)
+            }
+        }
+
+        if swimmer.breath == 0.0 {
+            damage_events.send(Damage {
+                entity,
+                amount: DROWNING_DAMAGE * delta_seconds,
+            });
+        }
+
+        if !swimmer.swimming {
+            continue;
+        }
+
+        // Treading keeps the feet at a depth where the head stays above the
+        // surface, swimming up can't lift the character out of the water.
+        let rise_limit = (depth - TREAD_DEPTH) * BUOYANCY;
+        let target_speed = match input.vertical {
+            vertical if vertical > 0.0 => (vertical * DIVE_SPEED).min(rise_limit),
+            vertical if vertical < 0.0 => vertical * DIVE_SPEED,
+            _ => rise_limit.min(FLOAT_SPEED),
+        };
+
+        let blend = (WATER_DRAG * delta_seconds).min(1.0);
+        vertical_speed.0 += (target_speed - vertical_speed.0) * blend;
+
+        controller.translation = Some(
+            input.movement * SWIM_SPEED * delta_seconds
+                + Vec3::Y * vertical_speed.0 * delta_seconds,
+        );
+
+        let facing = input.look.unwrap_or(input.movement);
+        if facing != Vec3::ZERO {
+            transform.look_to(-facing, Vec3::Y);
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::state::GameState;
+use crate::{camera_mode::free_fly_active, cutscene::cutscene_playing, state::GameState};
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
 pub enum InGameSet {
@@ -8,6 +8,31 @@ pub enum InGameSet {
     EntityUpdates,
 }
 
+/// Input sources like AI brains write `CharacterInput` in `Input`, or pick a
+/// destination that `Navigation` steers towards, the shared character
+/// controller consumes it in `Controller`.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+pub enum CharacterSet {
+    Input,
+    Navigation,
+    Controller,
+}
+
+/// Switches write their signals in `Emit` and the logic graph combines them
+/// in `Evaluate`, whatever reacts to a signal runs after it.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+pub enum SignalSet {
+    Emit,
+    Evaluate,
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+pub enum CameraSet {
+    Controller,
+    Rig,
+    Effects,
+}
+
 pub struct SchedulePlugin;
 
 impl Plugin for SchedulePlugin {
@@ -17,6 +42,32 @@ impl Plugin for SchedulePlugin {
             (InGameSet::UserInput, InGameSet::EntityUpdates)
                 .chain()
                 .run_if(in_state(GameState::InGame)),
-        );
+        )
+        .configure_sets(
+            Update,
+            (
+                CharacterSet::Input,
+                CharacterSet::Navigation,
+                CharacterSet::Controller,
+            )
+                .chain()
+                .in_set(InGameSet::EntityUpdates),
+        )
+        .configure_sets(
+            Update,
+            (SignalSet::Emit, SignalSet::Evaluate)
+                .chain()
+                .in_set(InGameSet::EntityUpdates)
+                .before(CharacterSet::Input),
+        )
+        .configure_sets(
+            Update,
+            (CameraSet::Controller, CameraSet::Rig, CameraSet::Effects).chain(),
+        )
+        .configure_sets(
+            Update,
+            (InGameSet::UserInput, CameraSet::Controller).run_if(not(free_fly_active)),
+        )
+        .configure_sets(Update, InGameSet::UserInput.run_if(not(cutscene_playing)));
     }
 }
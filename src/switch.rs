@@ -0,0 +1,189 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    interaction::{Interactable, Interacted},
+    schedule::SignalSet,
+    wiring::{SignalOutput, Signals},
+};
+
+const LEVER_SIZE: Vec3 = Vec3::new(0.08, 0.7, 0.08);
+const LEVER_ANGLE: f32 = 0.5;
+const LEVER_INTERACTION_RANGE: f32 = 1.4;
+/// Height above a pressure plate in which a character weighs it down.
+const PLATE_REACH: f32 = 0.3;
+
+#[derive(Deserialize, Clone, Copy)]
+pub enum SwitchKind {
+    /// Flips whenever a player pulls it.
+    Lever,
+    /// On while a character stands on a plate with these half extents.
+    PressurePlate(Vec3),
+    /// On while a character is inside an invisible box with these half
+    /// extents.
+    Trigger(Vec3),
+}
+
+/// Layout of a switch in a level file, it writes its state to `signal`.
+#[derive(Deserialize)]
+pub struct SwitchDefinition {
+    pub name: String,
+    pub kind: SwitchKind,
+    pub position: Vec3,
+    pub signal: String,
+}
+
+#[derive(Component)]
+pub struct Switch {
+    kind: SwitchKind,
+    on: bool,
+}
+
+pub struct SwitchPlugin;
+
+impl Plugin for SwitchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (pull_levers, press_switches, emit_switch_signals)
+                .chain()
+                .in_set(SignalSet::Emit),
+        );
+    }
+}
+
+/// Spawns a switch described in a level file.
+pub fn spawn_switch(
+    commands: &mut Commands,
+    definition: &SwitchDefinition,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    assets: &AssetServer,
+) {
+    let switch = (
+        Name::new(definition.name.clone()),
+        Switch {
+            kind: definition.kind,
+            on: false,
+        },
+        SignalOutput(definition.signal.clone()),
+    );
+
+    let material = materials.add(StandardMaterial {
+        base_color_texture: Some(assets.load("grid_textures/Pink/g2487.png")),
+        ..default()
+    });
+
+    match definition.kind {
+        SwitchKind::Lever => {
+            // Pivots around its foot, the mesh and collider sit above it.
+            let handle = (
+                PbrBundle {
+                    mesh: meshes.add(Cuboid::from_size(LEVER_SIZE)),
+                    material,
+                    transform: Transform::from_xyz(0.0, LEVER_SIZE.y * 0.5, 0.0),
+                    ..default()
+                },
+                RigidBody::Fixed,
+                Collider::cuboid(LEVER_SIZE.x * 0.5, LEVER_SIZE.y * 0.5, LEVER_SIZE.z * 0.5),
+            );
+
+            commands
+                .spawn((
+                    SpatialBundle::from_transform(lever_transform(definition.position, false)),
+                    switch,
+                    Interactable::new("Pull lever", LEVER_INTERACTION_RANGE),
+                ))
+                .with_children(|parent| {
+                    parent.spawn(handle);
+                });
+        }
+        SwitchKind::PressurePlate(half_extents) => {
+            commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(Cuboid::from_size(half_extents * 2.0)),
+                    material,
+                    transform: Transform::from_translation(definition.position),
+                    ..default()
+                },
+                switch,
+                RigidBody::Fixed,
+                Collider::cuboid(half_extents.x, half_extents.y, half_extents.z),
+            ));
+        }
+        SwitchKind::Trigger(_) => {
+            commands.spawn((
+                SpatialBundle::from_transform(Transform::from_translation(definition.position)),
+                switch,
+            ));
+        }
+    }
+}
+
+fn lever_transform(position: Vec3, on: bool) -> Transform {
+    let angle = match on {
+        true => -LEVER_ANGLE,
+        false => LEVER_ANGLE,
+    };
+
+    Transform::from_translation(position).with_rotation(Quat::from_rotation_x(angle))
+}
+
+fn pull_levers(
+    mut switch_query: Query<(&mut Switch, &mut Transform)>,
+    mut interacted_events: EventReader<Interacted>,
+) {
+    for event in interacted_events.read() {
+        let Ok((mut switch, mut transform)) = switch_query.get_mut(event.entity) else {
+            continue;
+        };
+
+        if !matches!(switch.kind, SwitchKind::Lever) {
+            continue;
+        }
+
+        switch.on = !switch.on;
+        *transform = lever_transform(transform.translation, switch.on);
+    }
+}
+
+/// Pressure plates and triggers are on while any character overlaps them.
+fn press_switches(
+    mut switch_query: Query<(&mut Switch, &GlobalTransform)>,
+    character_query: Query<(), With<KinematicCharacterController>>,
+    rapier_context: Res<RapierContext>,
+) {
+    for (mut switch, transform) in switch_query.iter_mut() {
+        let (center, half_extents) = match switch.kind {
+            SwitchKind::Lever => continue,
+            SwitchKind::PressurePlate(half_extents) => (
+                transform.translation() + Vec3::Y * (half_extents.y + PLATE_REACH * 0.5),
+                Vec3::new(half_extents.x, PLATE_REACH * 0.5, half_extents.z),
+            ),
+            SwitchKind::Trigger(half_extents) => (transform.translation(), half_extents),
+        };
+
+        let is_character = |entity| character_query.contains(entity);
+
+        switch.on = rapier_context
+            .intersection_with_shape(
+                center,
+                Quat::IDENTITY,
+                &Collider::cuboid(half_extents.x, half_extents.y, half_extents.z),
+                QueryFilter::default()
+                    .exclude_sensors()
+                    .predicate(&is_character),
+            )
+            .is_some();
+    }
+}
+
+fn emit_switch_signals(
+    switch_query: Query<(&Switch, &SignalOutput)>,
+    mut signals: ResMut<Signals>,
+) {
+    for (switch, output) in switch_query.iter() {
+        signals.set(&output.0, switch.on);
+    }
+}
@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+/// Layout of a water volume in a level file, the surface is the top of the
+/// box.
+#[derive(Deserialize)]
+pub struct WaterDefinition {
+    pub name: String,
+    pub position: Vec3,
+    pub half_extents: Vec3,
+}
+
+/// Sensor box filled with water up to `surface`.
+#[derive(Component)]
+pub struct Water {
+    surface: f32,
+    half_extents: Vec3,
+}
+
+impl Water {
+    /// How deep `point` is below the surface, `None` when it is outside the
+    /// volume horizontally or below its bottom.
+    pub fn depth(&self, transform: &GlobalTransform, point: Vec3) -> Option<f32> {
+        let local = transform.affine().inverse().transform_point3(point);
+        let inside = local.x.abs() <= self.half_extents.x
+            && local.z.abs() <= self.half_extents.z
+            && local.y >= -self.half_extents.y;
+
+        inside.then_some(self.surface - point.y)
+    }
+}
+
+/// Everything a water volume described in a level file needs.
+pub fn water_bundle(
+    definition: &WaterDefinition,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+) -> impl Bundle {
+    let half_extents = definition.half_extents;
+
+    (
+        PbrBundle {
+            mesh: meshes.add(Cuboid::from_size(half_extents * 2.0)),
+            material: materials.add(StandardMaterial {
+                base_color: Color::rgba(0.1, 0.35, 0.6, 0.5),
+                alpha_mode: AlphaMode::Blend,
+                ..default()
+            }),
+            transform: Transform::from_translation(definition.position),
+            ..default()
+        },
+        Name::new(definition.name.clone()),
+        Water {
+            surface: definition.position.y + half_extents.y,
+            half_extents,
+        },
+        Collider::cuboid(half_extents.x, half_extents.y, half_extents.z),
+        Sensor,
+    )
+}
@@ -0,0 +1,193 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::Deserialize;
+
+use crate::schedule::SignalSet;
+
+const NODE_RADIUS: f32 = 0.08;
+const ON_COLOR: Color = Color::GREEN;
+const OFF_COLOR: Color = Color::GRAY;
+
+/// Named on/off signals wiring switches to whatever reacts to them, a signal
+/// nobody writes is off.
+#[derive(Resource, Default)]
+pub struct Signals(HashMap<String, bool>);
+
+impl Signals {
+    pub fn get(&self, signal: &str) -> bool {
+        self.0.get(signal).copied().unwrap_or(false)
+    }
+
+    pub fn set(&mut self, signal: &str, on: bool) {
+        match self.0.get_mut(signal) {
+            Some(value) => *value = on,
+            None => {
+                self.0.insert(signal.to_owned(), on);
+            }
+        }
+    }
+}
+
+/// Writes its switch's state to a signal.
+#[derive(Component)]
+pub struct SignalOutput(pub String);
+
+/// Makes a door, lamp or platform react to a signal.
+#[derive(Component)]
+pub struct SignalInput(pub String);
+
+#[derive(Deserialize, Clone)]
+pub enum LogicGate {
+    /// On while all inputs are on.
+    And(Vec<String>),
+    /// On while any input is on.
+    Or(Vec<String>),
+    /// On while the input is on and for `seconds` after it turns off.
+    Timer { input: String, seconds: f32 },
+    /// Flips every time the input turns on.
+    Toggle(String),
+}
+
+impl LogicGate {
+    fn inputs(&self) -> &[String] {
+        match self {
+            LogicGate::And(inputs) | LogicGate::Or(inputs) => inputs,
+            LogicGate::Timer { input, .. } | LogicGate::Toggle(input) => {
+                std::slice::from_ref(input)
+            }
+        }
+    }
+}
+
+/// A node of the logic graph in a level file, `position` is only used to
+/// draw the graph.
+#[derive(Deserialize)]
+pub struct LogicDefinition {
+    pub gate: LogicGate,
+    pub output: String,
+    pub position: Vec3,
+}
+
+struct LogicNode {
+    gate: LogicGate,
+    output: String,
+    position: Vec3,
+    input_was_on: bool,
+    remaining: f32,
+    on: bool,
+}
+
+/// The logic nodes of the current level. They are evaluated in the order
+/// they are listed, so a node reading the output of one further down sees
+/// its value from the previous frame.
+#[derive(Resource, Default)]
+pub struct LogicGraph(Vec<LogicNode>);
+
+impl LogicGraph {
+    pub fn new(definitions: &[LogicDefinition]) -> LogicGraph {
+        let nodes = definitions
+            .iter()
+            .map(|definition| LogicNode {
+                gate: definition.gate.clone(),
+                output: definition.output.clone(),
+                position: definition.position,
+                input_was_on: false,
+                remaining: 0.0,
+                on: false,
+            })
+            .collect();
+
+        LogicGraph(nodes)
+    }
+}
+
+pub struct WiringPlugin;
+
+impl Plugin for WiringPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Signals>()
+            .init_resource::<LogicGraph>()
+            .add_systems(Update, evaluate_logic.in_set(SignalSet::Evaluate))
+            .add_systems(Update, draw_wiring);
+    }
+}
+
+fn evaluate_logic(mut graph: ResMut<LogicGraph>, mut signals: ResMut<Signals>, time: Res<Time>) {
+    for node in graph.0.iter_mut() {
+        let input_on = node
+            .gate
+            .inputs()
+            .first()
+            .is_some_and(|input| signals.get(input));
+
+        node.on = match &node.gate {
+            LogicGate::And(inputs) => inputs.iter().all(|input| signals.get(input)),
+            LogicGate::Or(inputs) => inputs.iter().any(|input| signals.get(input)),
+            LogicGate::Timer { seconds, .. } => {
+                node.remaining = match input_on {
+                    true => *seconds,
+                    false => (node.remaining - time.delta_seconds()).max(0.0),
+                };
+
+                node.remaining > 0.0
+            }
+            LogicGate::Toggle(_) => node.on ^ (input_on && !node.input_was_on),
+        };
+
+        node.input_was_on = input_on;
+        signals.set(&node.output, node.on);
+    }
+}
+
+/// Draws every wire from where a signal is written to where it is read, lit
+/// while the signal is on, and the logic nodes in between.
+fn draw_wiring(
+    mut gizmos: Gizmos,
+    graph: Res<LogicGraph>,
+    signals: Res<Signals>,
+    output_query: Query<(&SignalOutput, &GlobalTransform)>,
+    input_query: Query<(&SignalInput, &GlobalTransform)>,
+) {
+    let mut writers = HashMap::<&str, Vec<Vec3>>::new();
+    for (output, transform) in output_query.iter() {
+        writers
+            .entry(output.0.as_str())
+            .or_default()
+            .push(transform.translation());
+    }
+    for node in graph.0.iter() {
+        writers
+            .entry(node.output.as_str())
+            .or_default()
+            .push(node.position);
+    }
+
+    let readers = input_query
+        .iter()
+        .map(|(input, transform)| (input.0.as_str(), transform.translation()))
+        .chain(graph.0.iter().flat_map(|node| {
+            node.gate
+                .inputs()
+                .iter()
+                .map(|input| (input.as_str(), node.position))
+        }));
+
+    for (signal, to) in readers {
+        let color = match signals.get(signal) {
+            true => ON_COLOR,
+            false => OFF_COLOR,
+        };
+
+        for from in writers.get(signal).into_iter().flatten() {
+            gizmos.line(*from, to, color);
+        }
+    }
+
+    for node in graph.0.iter() {
+        let color = match node.on {
+            true => ON_COLOR,
+            false => OFF_COLOR,
+        };
+
+        gizmos.sphere(node.position, Quat::IDENTITY, NODE_RADIUS, color);
+    }
+}
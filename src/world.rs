@@ -1,11 +1,27 @@
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 
+use crate::{
+    cutscene::{CameraKeyframe, Cutscene, CutsceneTrigger},
+    lock_on::Targetable,
+};
+
 pub struct WorldPlugin;
 
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, (spawn_light, spawn_floor, spawn_obstacles).chain());
+        app.add_systems(
+            Startup,
+            (
+                spawn_light,
+                spawn_floor,
+                spawn_obstacles,
+                spawn_ramps,
+                spawn_pool,
+                spawn_cutscene_triggers,
+            )
+                .chain(),
+        );
     }
 }
 
@@ -68,6 +84,7 @@ fn spawn_obstacles(
         Name::new("Blue cube"),
         RigidBody::Fixed,
         Collider::cuboid(0.5, 0.5, 0.5),
+        Targetable,
     );
 
     let blue_cube2 = (
@@ -83,6 +100,7 @@ fn spawn_obstacles(
         Name::new("Blue cube"),
         RigidBody::Fixed,
         Collider::cuboid(0.5, 0.5, 0.5),
+        Targetable,
     );
 
     let blue_cube3 = (
@@ -98,6 +116,7 @@ fn spawn_obstacles(
         Name::new("Blue cube"),
         RigidBody::Fixed,
         Collider::cuboid(0.5, 0.5, 0.5),
+        Targetable,
     );
 
     let blue_cube4 = (
@@ -113,6 +132,7 @@ fn spawn_obstacles(
         Name::new("Blue cube"),
         RigidBody::Fixed,
         Collider::cuboid(0.5, 0.5, 0.5),
+        Targetable,
     );
 
     let blue_cube5 = (
@@ -128,6 +148,7 @@ fn spawn_obstacles(
         Name::new("Blue cube"),
         RigidBody::Fixed,
         Collider::cuboid(0.5, 0.5, 0.5),
+        Targetable,
     );
 
     let yellow_cube = (
@@ -143,6 +164,7 @@ fn spawn_obstacles(
         Name::new("Yellow cube"),
         RigidBody::Fixed,
         Collider::cuboid(0.75, 0.75, 0.75),
+        Targetable,
     );
 
     commands.spawn(blue_cube);
@@ -152,3 +174,144 @@ fn spawn_obstacles(
     commands.spawn(blue_cube5);
     commands.spawn(yellow_cube);
 }
+
+fn spawn_ramps(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    assets: Res<AssetServer>,
+) {
+    // The gentle ramp can be walked up, the steep one is over the climb
+    // angle of the movement profile and slides characters back down.
+    let ramps = [
+        ("Gentle ramp", Vec3::new(-3.0, 0.3, -3.5), 20.0_f32),
+        ("Steep ramp", Vec3::new(3.0, 0.6, -3.5), -55.0_f32),
+    ];
+
+    for (name, position, angle) in ramps {
+        let ramp = (
+            PbrBundle {
+                mesh: meshes.add(Cuboid::new(1.6, 0.1, 0.9)),
+                material: materials.add(StandardMaterial {
+                    base_color_texture: Some(assets.load("grid_textures/Grey/prototype.png")),
+                    ..default()
+                }),
+                transform: Transform::from_translation(position)
+                    .with_rotation(Quat::from_rotation_z(angle.to_radians())),
+                ..default()
+            },
+            Name::new(name),
+            RigidBody::Fixed,
+            Collider::cuboid(0.8, 0.05, 0.45),
+        );
+
+        commands.spawn(ramp);
+    }
+}
+
+/// Basin next to the floor, the level file fills it with water.
+fn spawn_pool(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    assets: Res<AssetServer>,
+) {
+    let material = materials.add(StandardMaterial {
+        base_color_texture: Some(assets.load("grid_textures/Grey/g1153.png")),
+        ..default()
+    });
+
+    let walls = [
+        (
+            "Pool bottom",
+            Vec3::new(5.5, -2.05, 0.0),
+            Vec3::new(1.6, 0.05, 2.1),
+        ),
+        (
+            "Pool wall",
+            Vec3::new(3.95, -1.0, 0.0),
+            Vec3::new(0.05, 1.0, 2.1),
+        ),
+        (
+            "Pool wall",
+            Vec3::new(7.05, -1.0, 0.0),
+            Vec3::new(0.05, 1.0, 2.1),
+        ),
+        (
+            "Pool wall",
+            Vec3::new(5.5, -1.0, 2.05),
+            Vec3::new(1.6, 1.0, 0.05),
+        ),
+        (
+            "Pool wall",
+            Vec3::new(5.5, -1.0, -2.05),
+            Vec3::new(1.6, 1.0, 0.05),
+        ),
+    ];
+
+    for (name, position, half_extents) in walls {
+        let wall = (
+            PbrBundle {
+                mesh: meshes.add(Cuboid::from_size(half_extents * 2.0)),
+                material: material.clone(),
+                transform: Transform::from_translation(position),
+                ..default()
+            },
+            Name::new(name),
+            RigidBody::Fixed,
+            Collider::cuboid(half_extents.x, half_extents.y, half_extents.z),
+        );
+
+        commands.spawn(wall);
+    }
+}
+
+fn spawn_cutscene_triggers(mut commands: Commands) {
+    let level_overview = Cutscene::new(vec![
+        CameraKeyframe {
+            time: 0.0,
+            position: Vec3::new(-6.0, 6.0, 6.0),
+            look_at: Vec3::new(0.0, 0.0, 0.0),
+            fov: std::f32::consts::FRAC_PI_3,
+        },
+        CameraKeyframe {
+            time: 3.0,
+            position: Vec3::new(0.5, 2.5, -6.0),
+            look_at: Vec3::new(0.5, 0.0, -2.5),
+            fov: std::f32::consts::FRAC_PI_4,
+        },
+        CameraKeyframe {
+            time: 6.0,
+            position: Vec3::new(5.5, 3.0, 0.5),
+            look_at: Vec3::new(2.0, 0.75, 2.0),
+            fov: std::f32::consts::FRAC_PI_4,
+        },
+        CameraKeyframe {
+            time: 8.0,
+            position: Vec3::new(-1.0, 3.0, 6.0),
+            look_at: Vec3::new(-2.5, 1.0, 2.5),
+            fov: std::f32::consts::FRAC_PI_4,
+        },
+    ]);
+
+    let level_overview = match level_overview {
+        Ok(cutscene) => cutscene,
+        Err(error) => {
+            error!("Could not create the level overview cutscene: {error}");
+            return;
+        }
+    };
+
+    let trigger = (
+        TransformBundle {
+            local: Transform::from_xyz(-2.5, 0.75, 2.5),
+            ..default()
+        },
+        Name::new("Level overview trigger"),
+        Collider::cuboid(0.75, 0.75, 0.75),
+        Sensor,
+        CutsceneTrigger(level_overview),
+    );
+
+    commands.spawn(trigger);
+}